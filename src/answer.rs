@@ -0,0 +1,82 @@
+// Puzzle answer: solvers return whatever integer type is natural to
+// them, or text
+
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+// integers compare by value, whatever their variant
+impl PartialEq for Answer {
+    fn eq(&self, other: &Answer) -> bool {
+        match (self, other) {
+            (Answer::Signed(a), Answer::Signed(b)) => a == b,
+            (Answer::Unsigned(a), Answer::Unsigned(b)) => a == b,
+            (Answer::Signed(a), Answer::Unsigned(b))
+                | (Answer::Unsigned(b), Answer::Signed(a)) =>
+                u128::try_from(*a).is_ok_and(|a| a == *b),
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+macro_rules! from_int {
+    ($variant:ident, $wide:ty, $($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(n: $t) -> Answer {
+                Answer::$variant(n as $wide)
+            }
+        })*
+    };
+}
+
+from_int!(Signed, i128, i32, i64, i128);
+from_int!(Unsigned, u128, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::Text(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(-12i64).to_string(), "-12");
+        assert_eq!(Answer::from(u128::MAX).to_string(), "340282366920938463463374607431768211455");
+        assert_eq!(Answer::from("abc").to_string(), "abc");
+    }
+    #[test]
+    fn test_eq() {
+        assert_eq!(Answer::from(12usize), Answer::from(12i64));
+        assert_eq!(Answer::from(12i64), Answer::from(12u32));
+        assert_ne!(Answer::from(-1i64), Answer::from(u128::MAX));
+        assert_ne!(Answer::from(12i64), Answer::from("12"));
+    }
+}
@@ -0,0 +1,89 @@
+// Known-good answers, stored as plain text lines of
+// '<day> <part> <answer>', with '#' starting a comment
+
+use std::collections::HashMap;
+
+use crate::error::{numbered_lines, parse_field, AocError, Result};
+
+pub struct Answers {
+    h: HashMap<(u32, u32), String>,
+}
+
+pub enum Check {
+    Pass,
+    Fail { expected: String },
+    // no stored answer for this day and part
+    Unknown,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Answers> {
+        let mut h = HashMap::new();
+        for (n, line) in numbered_lines(text) {
+            let content = line.split('#').next().unwrap().trim();
+            if content.is_empty() {
+                continue
+            }
+            let words: Vec<&str> = content.split_whitespace().collect();
+            let [day, part, answer] = words[..] else {
+                return Err(AocError::at(n, line, content, "expected '<day> <part> <answer>'"));
+            };
+            let key = (parse_field(n, line, day)?, parse_field(n, line, part)?);
+            if h.insert(key, answer.to_string()).is_some() {
+                return Err(AocError::at(n, line, content, "duplicate answer"));
+            }
+        }
+        Ok(Answers{ h })
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &str) -> Check {
+        match self.h.get(&(day, part)) {
+            None => Check::Unknown,
+            Some(expected) if expected == answer => Check::Pass,
+            Some(expected) => Check::Fail{ expected: expected.clone() },
+        }
+    }
+}
+
+impl Check {
+    pub fn is_fail(&self) -> bool {
+        matches!(self, Check::Fail{..})
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Check::Pass => "PASS",
+            Check::Fail{..} => "FAIL",
+            Check::Unknown => "-",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: &str = "
+# day part answer
+1 1 995
+1 2 5847   # trailing comment
+12 1 437
+";
+    #[test]
+    fn test_check() {
+        let answers = Answers::parse(ANSWERS).unwrap();
+        assert!(matches!(answers.check(1, 1, "995"), Check::Pass));
+        assert!(matches!(answers.check(1, 2, "5848"),
+                         Check::Fail{ ref expected } if expected == "5847"));
+        assert!(matches!(answers.check(12, 2, "1"), Check::Unknown));
+    }
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Answers::parse("1 1 995\n1 x 12\n").err(),
+                   Some(AocError::parse(2, 3, "x", "invalid number")));
+        assert_eq!(Answers::parse("1 1\n").err(),
+                   Some(AocError::parse(1, 1, "1 1", "expected '<day> <part> <answer>'")));
+        assert_eq!(Answers::parse("1 1 2\n1 1 3\n").err(),
+                   Some(AocError::parse(2, 1, "1 1 3", "duplicate answer")));
+    }
+}
@@ -0,0 +1,130 @@
+// Benchmarking of a solver over repeated runs
+
+use std::time::{Duration, Instant};
+
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::report::json_string;
+use crate::solver::{Params, Solver};
+
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    // samples must not be empty
+    pub fn new(samples: &[Duration]) -> Stats {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+
+        let median = if n.is_multiple_of(2) {
+            (sorted[n/2 - 1] + sorted[n/2]) / 2
+        } else {
+            sorted[n/2]
+        };
+        let secs: Vec<f64> = sorted.iter().map(|d| d.as_secs_f64()).collect();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let var = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+
+        Stats{
+            runs: n,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(var.sqrt()),
+        }
+    }
+}
+
+// run solver on text, once to warm up, then runs times
+pub fn bench(solver: &dyn Solver, part: u32, text: &str, params: &Params, runs: usize)
+             -> Result<(Answer, Stats)> {
+    let answer = solver.solve_with(part, text, params)?;
+
+    let samples: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            let r = solver.solve_with(part, text, params);
+            let elapsed = start.elapsed();
+            r.map(|_| elapsed)
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((answer, Stats::new(&samples)))
+}
+
+pub fn format_plain(day: u32, part: u32, answer: &Answer, s: &Stats) -> String {
+    format!("day {} part {}: answer {}, {} runs\n  min {:.3?}  median {:.3?}  mean {:.3?}  stddev {:.3?}",
+            day, part, answer, s.runs, s.min, s.median, s.mean, s.stddev)
+}
+
+pub fn format_json(day: u32, part: u32, answer: &Answer, s: &Stats) -> String {
+    format!("{{\"day\":{},\"part\":{},\"answer\":{},\"runs\":{},\"min_ns\":{},\"median_ns\":{},\"mean_ns\":{},\"stddev_ns\":{}}}",
+            day, part, json_string(&answer.to_string()), s.runs, s.min.as_nanos(), s.median.as_nanos(),
+            s.mean.as_nanos(), s.stddev.as_nanos())
+}
+
+pub const CSV_HEADER: &str = "day,part,answer,runs,min_ns,median_ns,mean_ns,stddev_ns";
+
+// answer quoted if needed, as text answers may contain commas
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn format_csv(day: u32, part: u32, answer: &Answer, s: &Stats) -> String {
+    format!("{},{},{},{},{},{},{},{}",
+            day, part, csv_field(&answer.to_string()), s.runs, s.min.as_nanos(), s.median.as_nanos(),
+            s.mean.as_nanos(), s.stddev.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(v: &[u64]) -> Vec<Duration> {
+        v.iter().map(|&m| Duration::from_millis(m)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let s = Stats::new(&ms(&[4, 2, 6, 8]));
+        assert_eq!(s.runs, 4);
+        assert_eq!(s.min, Duration::from_millis(2));
+        assert_eq!(s.median, Duration::from_millis(5));
+        assert_eq!(s.mean.as_micros(), 5000);
+        assert_eq!(s.stddev.as_micros(), 2236);
+
+        let s = Stats::new(&ms(&[3, 1, 2]));
+        assert_eq!(s.median, Duration::from_millis(2));
+        assert_eq!(s.mean.as_micros(), 2000);
+    }
+    #[test]
+    fn test_format() {
+        let s = Stats::new(&ms(&[1, 3]));
+        assert_eq!(format_csv(8, 1, &Answer::from(40u32), &s), "8,1,40,2,1000000,2000000,2000000,1000000");
+        assert_eq!(format_json(8, 1, &Answer::from(40u32), &s),
+                   "{\"day\":8,\"part\":1,\"answer\":\"40\",\"runs\":2,\"min_ns\":1000000,\"median_ns\":2000000,\"mean_ns\":2000000,\"stddev_ns\":1000000}");
+    }
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("123"), "123");
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+    #[test]
+    fn test_bench() {
+        let solver = crate::solver::lookup(1).unwrap();
+        let (answer, s) = bench(solver, 2, "L68\nL30\nR48\n", &Params::new(), 3).unwrap();
+        assert_eq!(answer, Answer::from(2u32));
+        assert_eq!(s.runs, 3);
+        assert!(bench(solver, 2, "X1\n", &Params::new(), 3).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+// Command line parsing
+
+use std::path::PathBuf;
+
+use crate::error::{AocError, Result};
+use crate::solver::{self, Params};
+
+pub enum Command {
+    // list available solvers
+    List,
+    // run one day and part on each input file, "-" being stdin
+    Run { day: u32, part: u32, inputs: Vec<PathBuf> },
+    // run every day and part, with inputs found in a directory
+    All { inputs: PathBuf },
+    // time repeated runs of one day and part
+    Bench { day: u32, part: u32, input: PathBuf, runs: usize },
+    // print this usage text
+    Help(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // human-readable (default)
+    Text,
+    // answers only, one per line
+    Plain,
+    // one JSON object per line
+    Json,
+    // bench only
+    Csv,
+}
+
+pub struct Options {
+    pub command: Command,
+    // file of known-good answers to check results against
+    pub expect: Option<PathBuf>,
+    pub format: Format,
+    // count of -v flags
+    pub verbosity: u8,
+    // number of threads for per-record work
+    pub jobs: usize,
+    // solver parameters, e.g. --from you
+    pub params: Params,
+}
+
+// whether --name is a parameter some solver takes, see Solver::params
+fn is_param(name: &str) -> bool {
+    solver::registry().iter().any(|s| s.params().contains(&name))
+}
+
+pub fn usage(prog: &str) -> String {
+    format!("Usage: {0} [options] <day num> <part num> <input_file|->...
+       {0} [options] all --inputs <dir>
+       {0} bench [--runs N] [--format json|csv] <day num> <part num> <input_file>
+       {0} list
+
+Options:
+  --expect <file>   check answers against file of '<day> <part> <answer>' lines
+  --runs <N>        number of timed runs for bench (default 10)
+  --format <fmt>    output format: plain (answers only) or json, and csv for bench
+  --jobs <N>        spread per-record work over N threads (needs the
+                    \"parallel\" feature; default 1)
+  -v, --verbose     log solver details to stderr (repeat for more, up to -vvv);
+                    AOC_LOG=<level> or AOC_LOG=<day>=<level>,.. overrides
+  -h, --help        print this help
+
+Solver parameters (see list for the days taking them):
+  --dial-size <n,..>   day 1: dial positions, 100 by default; several
+                       values turn several dials with the same input
+  --dial-start <n,..>  day 1: dial start position, 50 by default
+  --trace <file>       day 1: write every rotation of every dial to file,
+                       as JSON if its name ends in .json, CSV otherwise;
+                       with a single input, and not for bench
+  --targets <n,..>     day 1: instead of counting, print rotations visiting
+                       these positions from where the input leaves the dial
+  --minimise <what>    day 1: with --targets, fewest clicks (default) or
+                       fewest zero passes: clicks or passes
+  --from <label>       day 11: count paths from this device
+  --to <label>         day 11: count paths to this device
+  --via <a,b,..>       day 11: only paths visiting all these devices
+  --avoid <a,b,..>     day 11: only paths visiting none of these devices",
+            prog)
+}
+
+fn parse_num<T: std::str::FromStr>(what: &str, s: &str) -> Result<T> {
+    s.parse()
+        .map_err(|_| AocError::Usage(format!("Invalid {} {}", what, s)))
+}
+
+fn parse_format(s: &str) -> Result<Format> {
+    match s {
+        "plain" => Ok(Format::Plain),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(AocError::Usage(format!("Invalid format {}", s))),
+    }
+}
+
+pub fn parse_args(args: &[String]) -> Result<Options> {
+    let prog = args.first().map(|s| &s[..]).unwrap_or("aoc25");
+    let mut positional: Vec<&str> = vec![];
+    let mut expect = None;
+    let mut inputs = None;
+    let mut runs = None;
+    let mut format = Format::Text;
+    let mut verbosity: u8 = 0;
+    let mut jobs: usize = 1;
+    let mut params = Params::new();
+
+    let mut it = args.iter().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next()
+            .ok_or_else(|| AocError::Usage(format!("missing value for {}", arg)));
+        match &arg[..] {
+            "--expect" => expect = Some(PathBuf::from(value()?)),
+            "--inputs" => inputs = Some(PathBuf::from(value()?)),
+            "--runs" => runs = Some(parse_num("number of runs", value()?)?),
+            "--format" => format = parse_format(value()?)?,
+            "--jobs" | "-j" => jobs = match parse_num("number of jobs", value()?)? {
+                0 => return Err(AocError::Usage("Invalid number of jobs 0".to_string())),
+                n => n,
+            },
+            "-v" | "--verbose" => verbosity += 1,
+            "-vv" => verbosity += 2,
+            "-vvv" => verbosity += 3,
+            "-h" | "--help" => return Ok(Options{
+                command: Command::Help(usage(prog)), expect: None, format: Format::Text,
+                verbosity: 0, jobs: 1, params: Params::new(),
+            }),
+            _ if arg.strip_prefix("--").is_some_and(is_param) =>
+                params.set(&arg[2..], value()?),
+            _ if arg.starts_with('-') && arg != "-" =>
+                return Err(AocError::Usage(format!("unknown option {}\n{}", arg, usage(prog)))),
+            _ => positional.push(arg),
+        }
+    }
+
+    let command = match (&positional[..], inputs, runs) {
+        (["list"], None, None) => Command::List,
+        (["all"], Some(inputs), None) => Command::All{ inputs },
+        (["bench", day, part, input], None, runs) => Command::Bench{
+            day: parse_num("day", day)?,
+            part: parse_num("part", part)?,
+            input: PathBuf::from(input),
+            runs: match runs {
+                Some(0) => return Err(AocError::Usage("Invalid number of runs 0".to_string())),
+                Some(n) => n,
+                None => 10,
+            },
+        },
+        ([day, part, inputs @ ..], None, None) if !inputs.is_empty() => Command::Run{
+            day: parse_num("day", day)?,
+            part: parse_num("part", part)?,
+            inputs: inputs.iter().map(PathBuf::from).collect(),
+        },
+        _ => return Err(AocError::Usage(usage(prog))),
+    };
+    match (&command, format) {
+        (Command::Bench{..}, Format::Plain) =>
+            return Err(AocError::Usage("plain format is not available for bench".to_string())),
+        (Command::Run{..} | Command::All{..}, Format::Csv) =>
+            return Err(AocError::Usage("csv format is only available for bench".to_string())),
+        (Command::List, f) if f != Format::Text =>
+            return Err(AocError::Usage("--format is not available for list".to_string())),
+        _ => (),
+    }
+    if matches!(command, Command::List | Command::All{..}) && !params.is_empty() {
+        return Err(AocError::Usage(
+            "solver parameters are only available for a single day".to_string()));
+    }
+    // every solve would rewrite the trace file
+    if params.get("trace").is_some()
+        && !matches!(&command, Command::Run{ inputs, .. } if inputs.len() == 1) {
+        return Err(AocError::Usage(
+            "--trace is only available when running a single input".to_string()));
+    }
+
+    Ok(Options{ command, expect, format, verbosity, jobs, params })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|w| w.to_string()).collect()
+    }
+
+    fn paths(v: &[&str]) -> Vec<PathBuf> {
+        v.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let opts = parse_args(&args("aoc25 3 2 input3 --expect answers")).unwrap();
+        assert!(matches!(opts.command,
+                         Command::Run{ day: 3, part: 2, ref inputs } if inputs == &paths(&["input3"])));
+        assert_eq!(opts.expect, Some(PathBuf::from("answers")));
+
+        let opts = parse_args(&args("aoc25 3 2 a - b")).unwrap();
+        assert!(matches!(opts.command,
+                         Command::Run{ ref inputs, .. } if inputs == &paths(&["a", "-", "b"])));
+
+        let opts = parse_args(&args("aoc25 all --inputs src")).unwrap();
+        assert!(matches!(opts.command, Command::All{ ref inputs } if inputs.to_str() == Some("src")));
+        assert!(opts.expect.is_none());
+
+        assert!(matches!(parse_args(&args("aoc25 list")).unwrap().command, Command::List));
+        assert!(matches!(parse_args(&args("aoc25 3 --help")).unwrap().command,
+                         Command::Help(ref text) if text.starts_with("Usage:")));
+
+        let opts = parse_args(&args("aoc25 bench 8 1 input8 --runs 5 --format csv")).unwrap();
+        assert!(matches!(opts.command, Command::Bench{ day: 8, part: 1, runs: 5, .. }));
+        assert_eq!(opts.format, Format::Csv);
+        let opts = parse_args(&args("aoc25 bench 8 1 input8")).unwrap();
+        assert!(matches!(opts.command, Command::Bench{ runs: 10, .. }));
+        assert_eq!(opts.format, Format::Text);
+        let opts = parse_args(&args("aoc25 --format json 8 1 input8")).unwrap();
+        assert_eq!(opts.format, Format::Json);
+        assert_eq!(opts.verbosity, 0);
+        let opts = parse_args(&args("aoc25 -vv 8 1 input8 -v")).unwrap();
+        assert_eq!(opts.verbosity, 3);
+        assert_eq!(opts.jobs, 1);
+        let opts = parse_args(&args("aoc25 --jobs 4 10 2 input10")).unwrap();
+        assert_eq!(opts.jobs, 4);
+        assert!(opts.params.is_empty());
+        let opts = parse_args(&args("aoc25 11 2 input11 --from you --via a,b")).unwrap();
+        assert_eq!(opts.params.get("from"), Some("you"));
+        assert_eq!(opts.params.list("via"), Some(vec!["a", "b"]));
+        let opts = parse_args(&args("aoc25 1 1 input1 --dial-size 60,100")).unwrap();
+        assert_eq!(opts.params.list_of::<u64>("dial-size"), Ok(Some(vec![60, 100])));
+        // every parameter of every solver
+        for s in solver::registry() {
+            for p in s.params() {
+                let line = format!("aoc25 {} 1 input --{} 1", s.day(), p);
+                assert_eq!(parse_args(&args(&line)).unwrap().params.get(p), Some("1"));
+            }
+        }
+    }
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse_args(&args("aoc25 3 2")).is_err());
+        assert!(parse_args(&args("aoc25 x 2 input")).is_err());
+        assert!(parse_args(&args("aoc25 3 2 input --expect")).is_err());
+        assert!(parse_args(&args("aoc25 3 2 input --frobnicate 1")).is_err());
+        assert!(parse_args(&args("aoc25 3 2 input -x")).is_err());
+        assert!(parse_args(&args("aoc25 3 2 input --jobs 0")).is_err());
+        assert!(parse_args(&args("aoc25 1 1 a b --trace t.csv")).is_err());
+        assert!(parse_args(&args("aoc25 bench 1 1 a --trace t.csv")).is_err());
+        assert!(parse_args(&args("aoc25 all")).is_err());
+        assert!(parse_args(&args("aoc25 bench 8 1 input8 --runs 0")).is_err());
+        assert!(parse_args(&args("aoc25 8 1 input8 --runs 3")).is_err());
+        assert!(parse_args(&args("aoc25 8 1 input8 --format csv")).is_err());
+        assert!(parse_args(&args("aoc25 bench 8 1 input8 --format xml")).is_err());
+        assert!(parse_args(&args("aoc25 bench 8 1 input8 --format plain")).is_err());
+        assert!(parse_args(&args("aoc25 list --format json")).is_err());
+        assert!(parse_args(&args("aoc25 11 2 input11 --from")).is_err());
+        assert!(parse_args(&args("aoc25 11 2 input11 --nowhere out")).is_err());
+        assert!(parse_args(&args("aoc25 all --inputs src --to out")).is_err());
+    }
+}
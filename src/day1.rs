@@ -1,60 +1,169 @@
-use regex::Regex;
+use std::fs;
 
-pub fn parse_1(text: &str) -> i64 {
-    let re = Regex::new(r"([LR])(\d+)").unwrap();
-    let mut pos: i32 = 50;
-    let mut count_zero: i32 = 0;
+use crate::answer::Answer;
+use crate::dial::{Dial, Step, Turn};
+use crate::error::{AocError, Result};
+use crate::log::trace;
+use crate::parser::{map, one_of, pair, parse_lines, uint};
+use crate::report::json_string;
+use crate::solver::{DaySolver, Params};
 
-    for caps in re.captures_iter(text) {
-        let right: bool = &caps[1] == "R";
-        let count: i32 = caps[2].parse().unwrap();
+pub const SOLVER: DaySolver = DaySolver::new("day1", 1, &[parse_1, parse_2])
+    .with_params(&["dial-size", "dial-start", "trace", "targets", "minimise"],
+                 &[dials_1, dials_2]);
 
-        pos += if right { count } else { 100-count };
-        pos %= 100;
+// list of rotations
+fn parse_text(text: &str) -> Result<Vec<(Turn, u64)>> {
+    // e.g. L68
+    let rotation = pair(one_of("LR"), uint());
+    parse_lines(text, map(rotation, |(dir, count)|
+                          (if dir == 'R' { Turn::Right } else { Turn::Left }, count)))
+}
 
-        if pos == 0 {
-            count_zero += 1;
-        }
+// dials from --dial-size and --dial-start, 100 and 50 by default; a
+// single value applies to every dial
+fn dials(params: &Params) -> Result<Vec<Dial>> {
+    let sizes: Vec<u64> = params.list_of("dial-size")?.unwrap_or(vec![100]);
+    let starts: Vec<u64> = params.list_of("dial-start")?.unwrap_or(vec![50]);
+    let n = sizes.len().max(starts.len());
+    if n == 0 || ![1, n].contains(&sizes.len()) || ![1, n].contains(&starts.len()) {
+        return Err(AocError::Usage(
+            "--dial-size and --dial-start must list as many dials".to_string()));
     }
+    (0..n).map(|k| {
+        let (size, start) = (sizes[k % sizes.len()], starts[k % starts.len()]);
+        if start >= size {
+            return Err(AocError::Usage(
+                format!("dial start {} out of range for a dial of size {}", start, size)));
+        }
+        Ok(Dial::new(size, start))
+    }).collect()
+}
 
-    count_zero as i64
+// one trace record: rotation number (from 1), dial number (from 0), and what the rotation did
+type Record = (usize, usize, Step);
+
+const TRACE_FIELDS: [&str; 8] = ["step", "dial", "start", "dir", "clicks", "end", "landed",
+                                 "passes"];
+
+fn trace_values(&(step, dial, s): &Record) -> [String; 8] {
+    [step.to_string(), dial.to_string(), s.start.to_string(), s.turn.to_string(),
+     s.clicks.to_string(), s.end.to_string(), s.landed.to_string(), s.passes.to_string()]
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let re = Regex::new(r"([LR])(\d+)").unwrap();
-    let mut pos: i32 = 50;
-    let mut count_zero: i32 = 0;
+fn trace_csv(records: &[Record]) -> String {
+    let mut out = TRACE_FIELDS.join(",") + "\n";
+    for r in records {
+        out += &(trace_values(r).join(",") + "\n");
+    }
+    out
+}
 
-    for caps in re.captures_iter(text) {
-        let right: bool = &caps[1] == "R";
-        let mut count: i32 = caps[2].parse().unwrap();
+// array of objects, one per line
+fn trace_json(records: &[Record]) -> String {
+    let objects: Vec<String> = records.iter()
+        .map(|r| {
+            let fields: Vec<String> = TRACE_FIELDS.iter()
+                .zip(trace_values(r))
+                .map(|(name, v)| match *name {
+                    "dir" => format!("\"{}\":{}", name, json_string(&v)),
+                    _ => format!("\"{}\":{}", name, v),
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        })
+        .collect();
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
 
-        // full dial rotations
-        count_zero += count / 100;
-        count %= 100;
+// JSON if the file name ends in .json, CSV otherwise
+fn write_trace(name: &str, records: &[Record]) -> Result<()> {
+    let text = if name.ends_with(".json") { trace_json(records) } else { trace_csv(records) };
+    fs::write(name, text)
+        .map_err(|e| AocError::Io(format!("failed to write {}: {}", name, e)))
+}
 
-        if count == 0 {
-            continue
+// rotations visiting --targets in order, from where the input leaves
+// the dial, one per line; --minimise chooses between fewest clicks (the
+// default) and fewest zero passes. Totals go to stderr, so that the
+// answer can be replayed as input.
+fn plan_targets(dial: &Dial, params: &Params) -> Result<Option<Answer>> {
+    let min_passes = match params.get("minimise") {
+        None | Some("clicks") => false,
+        Some("passes") => true,
+        Some(v) => return Err(AocError::Usage(
+            format!("invalid value for --minimise: {} (expected clicks or passes)", v))),
+    };
+    let Some(targets) = params.list_of::<u64>("targets")? else {
+        if params.get("minimise").is_some() {
+            return Err(AocError::Usage("--minimise needs --targets".to_string()));
         }
+        return Ok(None)
+    };
+    if let Some(t) = targets.iter().find(|&&t| t >= dial.size()) {
+        return Err(AocError::Usage(
+            format!("target {} out of range for a dial of size {}", t, dial.size())));
+    }
+    let plan = dial.plan(&targets, min_passes);
+    eprintln!("plan from {}: {} rotations, {} clicks, {} zero passes",
+              dial.position(), plan.rotations.len(), plan.clicks, plan.passes);
+    Ok(Some(plan.to_string().trim_end().into()))
+}
 
-        if !right {
-            if pos > 0 && count >= pos {
-                count_zero += 1
+// turn every dial by each rotation, then count zero landings (part 1)
+// or zero passes (part 2) per dial; several dials give a comma-separated
+// list. With --targets, plan rotations instead, the same for both parts.
+fn turn_dials(text: &str, params: &Params, count_passes: bool) -> Result<Answer> {
+    let mut dials = dials(params)?;
+    let trace_file = params.get("trace");
+    let mut records = vec![];
+    for (k, (turn, clicks)) in parse_text(text)?.into_iter().enumerate() {
+        for (d, dial) in dials.iter_mut().enumerate() {
+            let step = dial.rotate(turn, clicks);
+            if trace_file.is_some() {
+                records.push((k + 1, d, step));
             }
-        } else if count + pos >= 100 {
-            count_zero += 1
         }
-
-        pos += if right { count } else { 100-count };
-        pos %= 100;
+        trace!("{:?} {} -> pos {:?}", turn, clicks,
+               dials.iter().map(|d| d.position()).collect::<Vec<_>>());
     }
+    if let Some(name) = trace_file {
+        write_trace(name, &records)?;
+    }
+    if let [dial] = &dials[..] {
+        if let Some(plan) = plan_targets(dial, params)? {
+            return Ok(plan)
+        }
+    } else if params.get("targets").is_some() {
+        return Err(AocError::Usage("--targets needs a single dial".to_string()));
+    }
+    let count = |d: &Dial| if count_passes { d.passes() } else { u128::from(d.landings()) };
+    Ok(match &dials[..] {
+        [dial] => count(dial).into(),
+        _ => dials.iter().map(|d| count(d).to_string()).collect::<Vec<_>>().join(",").into(),
+    })
+}
 
-    count_zero as i64
+fn dials_1(text: &str, params: &Params) -> Result<Answer> {
+    turn_dials(text, params, false)
+}
+
+fn dials_2(text: &str, params: &Params) -> Result<Answer> {
+    turn_dials(text, params, true)
+}
+
+pub fn parse_1(text: &str) -> Result<Answer> {
+    dials_1(text, &Params::new())
+}
+
+pub fn parse_2(text: &str) -> Result<Answer> {
+    dials_2(text, &Params::new())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::Lcg;
 
     const INPUT_TEXT_1: &str = "
 L68
@@ -70,10 +179,180 @@ L82
 ";
     #[test]
     fn test1_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 3);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(3)));
     }
     #[test]
     fn test1_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 6);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(6)));
+    }
+    #[test]
+    fn test1_errors() {
+        assert_eq!(parse_1("L68\nX30\n"),
+                   Err(AocError::parse(2, 1, "X30", "expected one of \"LR\"")));
+        assert_eq!(parse_1("L68\nR\n"),
+                   Err(AocError::parse(2, 2, "", "expected number")));
+        assert_eq!(parse_2("\nR99999999999999999999\n"),
+                   Err(AocError::parse(2, 2, "99999999999999999999", "number out of range")));
+    }
+    #[test]
+    fn test1_dials() {
+        let params = |size: &str, start: &str| {
+            let mut p = Params::new();
+            p.set("dial-size", size);
+            p.set("dial-start", start);
+            p
+        };
+        assert_eq!(dials_1(INPUT_TEXT_1, &params("100", "50")), Ok(Answer::from(3)));
+        assert_eq!(dials_2(INPUT_TEXT_1, &params("100,60", "50")), Ok(Answer::from("6,6")));
+        assert_eq!(dials_1(INPUT_TEXT_1, &params("100,60", "50,32")), Ok(Answer::from("3,0")));
+        assert!(dials_1(INPUT_TEXT_1, &params("100,60", "50,30,10")).is_err());
+        assert!(dials_1(INPUT_TEXT_1, &params("60", "60")).is_err());
+        assert!(dials_1(INPUT_TEXT_1, &params("0", "0")).is_err());
+        assert!(dials_1(INPUT_TEXT_1, &params("x", "0")).is_err());
+        assert!(matches!(dials_1(INPUT_TEXT_1, &params("60", "60")), Err(AocError::Usage(_))));
+        // no overflow on the largest values
+        assert_eq!(parse_2("R18446744073709551615\n"), Ok(Answer::from(184467440737095516u64)));
+        let max = u64::MAX.to_string();
+        assert_eq!(dials_2(&format!("R{}\nR2\n", max), &params(&max, &(u64::MAX - 1).to_string())),
+                   Ok(Answer::from(2)));
+    }
+
+    // reference: turn the dial one click at a time, returning zero
+    // landings and zero passes
+    fn clicks_oracle(size: u64, start: u64, rotations: &[(Turn, u64)]) -> (u64, u64) {
+        let (mut pos, mut landings, mut passes) = (start, 0, 0);
+        for &(turn, clicks) in rotations {
+            for _ in 0..clicks {
+                pos = match turn {
+                    Turn::Right => (pos + 1) % size,
+                    Turn::Left => (pos + size - 1) % size,
+                };
+                if pos == 0 {
+                    passes += 1;
+                }
+            }
+            if pos == 0 {
+                landings += 1;
+            }
+        }
+        (landings, passes)
+    }
+
+    // random rotations, favouring the edge cases: L0/R0, whole turns,
+    // and stopping on 0
+    fn random_rotations(size: u64, start: u64, n: usize, rng: &mut Lcg) -> Vec<(Turn, u64)> {
+        let mut next = |range: u64| rng.below(range);
+        let mut pos = start;
+        let mut v = vec![];
+        for _ in 0..n {
+            let turn = if next(2) == 0 { Turn::Left } else { Turn::Right };
+            let clicks = match next(5) {
+                0 => 0,
+                1 => size * next(4),
+                // back to 0, maybe after whole turns
+                2 => size * next(3) + match turn {
+                    Turn::Left => pos,
+                    Turn::Right => (size - pos) % size,
+                },
+                _ => next(3 * size + 1),
+            };
+            let mut dial = Dial::new(size, pos);
+            dial.rotate(turn, clicks);
+            pos = dial.position();
+            v.push((turn, clicks));
+        }
+        v
+    }
+
+    fn rotations_text(rotations: &[(Turn, u64)]) -> String {
+        rotations.iter()
+            .map(|&(turn, clicks)| format!("{}{}\n", turn, clicks))
+            .collect()
+    }
+
+    #[test]
+    fn test1_oracle() {
+        let mut rng = Lcg::new(1);
+        for _ in 0..200 {
+            let rotations = random_rotations(100, 50, 20, &mut rng);
+            let text = rotations_text(&rotations);
+            let (landings, passes) = clicks_oracle(100, 50, &rotations);
+            assert_eq!(parse_1(&text), Ok(Answer::from(landings)), "{}", text);
+            assert_eq!(parse_2(&text), Ok(Answer::from(passes)), "{}", text);
+        }
+    }
+    #[test]
+    fn test1_oracle_dials() {
+        let mut rng = Lcg::new(2);
+        for size in [1, 2, 3, 7, 60, 100] {
+            for start in [0, size / 2, size - 1] {
+                let rotations = random_rotations(size, start, 30, &mut rng);
+                let text = rotations_text(&rotations);
+                let mut params = Params::new();
+                params.set("dial-size", &size.to_string());
+                params.set("dial-start", &start.to_string());
+                let (landings, passes) = clicks_oracle(size, start, &rotations);
+                assert_eq!(dials_1(&text, &params), Ok(Answer::from(landings)), "{}", text);
+                assert_eq!(dials_2(&text, &params), Ok(Answer::from(passes)), "{}", text);
+            }
+        }
+    }
+    #[test]
+    fn test1_targets() {
+        let mut params = Params::new();
+        params.set("targets", "82,52,0");
+        assert_eq!(dials_1("", &params), Ok(Answer::from("R32\nL30\nR48")));
+        // plans start where the input leaves the dial
+        params.set("targets", "20");
+        assert_eq!(dials_1("R40\n", &params), Ok(Answer::from("R30")));
+        assert_eq!(dials_2("R40\n", &params), Ok(Answer::from("R30")));
+        // the long way round avoids 0, in either part
+        params.set("minimise", "passes");
+        assert_eq!(dials_1("R40\n", &params), Ok(Answer::from("L70")));
+        assert_eq!(dials_2("R40\n", &params), Ok(Answer::from("L70")));
+        params.set("minimise", "clicks");
+        assert_eq!(dials_2("R40\n", &params), Ok(Answer::from("R30")));
+        params.set("minimise", "time");
+        assert!(matches!(dials_1("R40\n", &params), Err(AocError::Usage(_))));
+        let mut no_targets = Params::new();
+        no_targets.set("minimise", "passes");
+        assert!(matches!(dials_1("R40\n", &no_targets), Err(AocError::Usage(_))));
+        // the plan replays to the targets
+        params.set("minimise", "passes");
+        params.set("targets", "82,52,0,30,55,99");
+        let Ok(Answer::Text(plan)) = dials_2(INPUT_TEXT_1, &params) else { panic!() };
+        assert_eq!(parse_1(&(INPUT_TEXT_1.to_string() + &plan)), Ok(Answer::from(4)));
+        params.set("targets", "100");
+        assert!(dials_1("", &params).is_err());
+        params.set("dial-size", "100,60");
+        params.set("targets", "10");
+        assert!(dials_1("", &params).is_err());
+    }
+    #[test]
+    fn test1_trace_steps() {
+        let mut d = Dial::new(100, 50);
+        assert_eq!(d.rotate(Turn::Left, 150),
+                   Step{ start: 50, turn: Turn::Left, clicks: 150, end: 0, landed: true,
+                         passes: 2 });
+        // not moving from 0 still lands there
+        assert_eq!(d.rotate(Turn::Right, 0),
+                   Step{ start: 0, turn: Turn::Right, clicks: 0, end: 0, landed: true,
+                         passes: 0 });
+        assert_eq!(trace_values(&(3, 1, d.rotate(Turn::Right, 5))),
+                   ["3", "1", "0", "R", "5", "5", "false", "0"]);
+    }
+    #[test]
+    fn test1_trace() {
+        let steps: Vec<Record> = parse_text("L68\nR48\n").unwrap().into_iter()
+            .enumerate()
+            .scan(Dial::new(100, 50),
+                  |d, (k, (turn, clicks))| Some((k + 1, 0, d.rotate(turn, clicks))))
+            .collect();
+        assert_eq!(trace_csv(&steps), "step,dial,start,dir,clicks,end,landed,passes\n\
+                                       1,0,50,L,68,82,false,1\n\
+                                       2,0,82,R,48,30,false,1\n");
+        assert_eq!(trace_json(&steps[..1]),
+                   "[\n{\"step\":1,\"dial\":0,\"start\":50,\"dir\":\"L\",\"clicks\":68,\
+                    \"end\":82,\"landed\":false,\"passes\":1}\n]\n");
     }
 }
@@ -1,5 +1,14 @@
-use std::collections::HashSet;
-use lru::LruCache;
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::gf2;
+use crate::ilp;
+use crate::log::{debug, info, trace};
+use crate::par;
+use crate::parser::{braces, brackets, comma_list, many0, many1, map, one_of,
+                    pair, parens, parse_lines, uint, verify, ws};
+use crate::solver::DaySolver;
+
+pub const SOLVER: DaySolver = DaySolver::new("day10", 10, &[parse_1, parse_2]);
 
 struct Machine {
     key: usize,
@@ -7,202 +16,191 @@ struct Machine {
     joltage: Vec<usize>,
 }
 
-fn parse_key(s: &[u8]) -> usize {
-    let mut key = 0;
-    for &c in s.iter().rev() {
-        key *= 2;
-        key += if c == b'#' { 1 } else { 0 };
-    }
-    key
-}
-
-fn parse_nums(s: &str) -> Vec<usize> {
-    s.split(",")
-        .map(|numstr| numstr.parse::<usize>().unwrap())
-        .collect()
-}
-
-fn parse_button(s: &str) -> usize {
-    parse_nums(s)
-        .iter()
-        .map(|&n| 2usize.pow(n as u32))
-        .reduce(|acc, n| acc|n).unwrap()
-}
-
-fn parse_text(text: &str) -> Vec<Machine> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let mut machine = Machine{key: 0, buttons: vec![], joltage: vec![]};
-            line.split(" ")
-                .for_each(|word| {
-                    let w = word.as_bytes();
-                    let s = &w[1..w.len()-1];
-                    match w[0] {
-                        b'[' => machine.key = parse_key(s),
-                        b'(' => machine.buttons.push(
-                            parse_button(str::from_utf8(s).unwrap())),
-                        b'{' => machine.joltage =
-                            parse_nums(str::from_utf8(s).unwrap()),
-                        _ => (),
-                    }
-                });
-            machine
-        })
-        .collect()
+// e.g. [.##.] (3) (1,3) (2) {3,5,4,7}
+fn parse_text(text: &str) -> Result<Vec<Machine>> {
+    let lights = verify(many1(one_of(".#")),
+                        |v| v.len() <= usize::BITS as usize,
+                        "too many lights");
+    let key = map(lights, |v| v.iter().rev()
+                  .fold(0, |key, &c| key * 2 + usize::from(c == '#')));
+    let indices = verify(comma_list(uint::<usize>()),
+                         |v| v.iter().all(|&k| k < usize::BITS as usize),
+                         "light index out of range");
+    let button = map(indices, |v| v.iter().fold(0, |acc, &k| acc | 1 << k));
+    // one counter per bit of a button mask
+    let joltage = verify(comma_list(uint()),
+                         |v| v.len() <= usize::BITS as usize,
+                         "too many joltage levels");
+    let machine = pair(brackets(key),
+                       pair(many0(ws(parens(button))),
+                            ws(braces(joltage))));
+    let machine = map(machine, |(key, (buttons, joltage))| Machine{key, buttons, joltage});
+    parse_lines(text, machine)
 }
 
-fn solve_machine(machine: &Machine) -> usize {
-    // breadth-first search over reachable states
-    let mut states: HashSet<usize> = HashSet::new();
-    let mut depth: usize = 0;
-    let mut cur_states: Vec<usize> = vec![0; 1]; // start state
-
-    while !states.contains(&machine.key) {
-        let next_states: Vec<usize> = cur_states
-            .iter()
-            .flat_map(|&s| machine.buttons
-                      .iter()
-                      .map(move |b| s^b))
-            .filter(|&s| !states.contains(&s))
-            .collect();
-        assert!(!next_states.is_empty());
-
-        next_states.iter().for_each(|&s|{
-            states.insert(s);
-        });
-        depth += 1;
-        cur_states = next_states;
+fn solve_machine(machine: &Machine) -> Result<usize> {
+    // toggling is XOR, i.e. addition over GF(2): pressing a button
+    // twice is useless, so look for the smallest set of buttons whose
+    // masks add up to the key
+    if machine.buttons.len() > 64 {
+        return Err(AocError::Input("more than 64 buttons".to_string()));
     }
-    depth
+    let buttons: Vec<u64> = machine.buttons.iter().map(|&b| b as u64).collect();
+    let solutions = gf2::solve(&buttons, machine.key as u64)
+        .ok_or_else(|| AocError::Input(
+            "light pattern cannot be reached with these buttons".to_string()))?;
+    trace!("{} free buttons", solutions.null_space.len());
+    let presses = solutions.min_weight().count_ones() as usize;
+    debug!("machine needs {} presses", presses);
+    Ok(presses)
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let machines = parse_text(text);
-    machines
-        .iter()
-        .map(solve_machine)
-        .sum::<usize>() as i64
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let machines = parse_text(text)?;
+    Ok(par::map(&machines, solve_machine)
+        .into_iter()
+        .sum::<Result<usize>>()?
+        .into())
 }
 
-fn min_sol(a: Option<usize>, b: Option<usize>) -> Option<usize> {
-    match (a, b) {
-        (Some(va), Some(vb)) => Some(std::cmp::min(va, vb)),
-        (Some(_), None) => a,
-        (None, Some(_)) => b,
-        (None, None) => None,
-    }
+fn solve_joltage(machine: &Machine) -> Result<usize> {
+    // one equation per counter: presses of the buttons wired to it
+    // add up to its joltage level
+    let n_counters = machine.joltage.len();
+    let a: Vec<Vec<u64>> = (0..n_counters)
+        .map(|k| machine.buttons
+             .iter()
+             .map(|b| ((b >> k) & 1) as u64)
+             .collect())
+        .collect();
+    let target: Vec<u64> = machine.joltage.iter().map(|&j| j as u64).collect();
+    let presses = ilp::min_sum(&a, &target)
+        .ok_or_else(|| AocError::Input(
+            format!("joltage levels {:?} cannot be reached", machine.joltage)))?;
+    trace!("presses per button: {:?}", presses);
+    Ok(presses.iter().sum::<u64>() as usize)
 }
 
-fn add_sol(a: Option<usize>, b: Option<usize>) -> Option<usize> {
-    match (a, b) {
-        (Some(va), Some(vb)) => Some(va+vb),
-        _ => None,
-    }
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let machines = parse_text(text)?;
+    Ok(par::map(&machines, solve_joltage)
+        .into_iter()
+        .inspect(|r| if let Ok(r) = r { info!("machine needs {} presses", r) })
+        .sum::<Result<usize>>()?
+        .into())
 }
 
-type Cache = LruCache<(usize, Vec<usize>), Option<usize>>;
-
-fn subsolve(buttons: &[Vec<usize>], target: &[usize],
-            max_presses: Option<usize>,
-            cache: &mut Cache) -> Option<usize> {
-    let n_buttons = buttons.len();
-    let n_counters = target.len();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lru::LruCache;
+
+    // reference solver: the original dynamic programming search, like
+    // with the coin change problem but in multiple dimensions
+    fn min_sol(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        match (a, b) {
+            (Some(va), Some(vb)) => Some(std::cmp::min(va, vb)),
+            (Some(_), None) => a,
+            (None, Some(_)) => b,
+            (None, None) => None,
+        }
+    }
 
-    let key = (n_buttons, target.to_vec());
-    if let Some(r) = cache.get(&key) {
-        return *r;
+    fn add_sol(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        match (a, b) {
+            (Some(va), Some(vb)) => Some(va+vb),
+            _ => None,
+        }
     }
 
-    let r =
-        if target.iter().all(|&n| n == 0) {
-            println!("#");
-            Some(0)
-        } else if n_buttons == 0 {
-            None
-        } else {
-            let b = &buttons[0];
-            let max_multiplier = (0..n_counters)
-                .map(|k| if b[k] > target[k] { 0 }
-                     else if b[k] > 0 { target[k] }
-                     else { usize::MAX })
-                .min().unwrap();
-            let max_multiplier =
-                min_sol(Some(max_multiplier), max_presses).unwrap();
-            assert!(max_multiplier < usize::MAX);
-
-            let mut sub_target: Vec<usize> = vec![0; n_counters];
-            let mut sub_best = None;
-            for m in (0..=max_multiplier).rev() {
-                for k in 0..n_counters {
-                    sub_target[k] = target[k] - m*b[k];
+    type Cache = LruCache<(usize, Vec<usize>), Option<usize>>;
+
+    fn subsolve(buttons: &[Vec<usize>], target: &[usize],
+                max_presses: Option<usize>,
+                cache: &mut Cache) -> Option<usize> {
+        let n_buttons = buttons.len();
+        let n_counters = target.len();
+
+        let key = (n_buttons, target.to_vec());
+        if let Some(r) = cache.get(&key) {
+            return *r;
+        }
+
+        let r =
+            if target.iter().all(|&n| n == 0) {
+                trace!("solution found");
+                Some(0)
+            } else if n_buttons == 0 {
+                None
+            } else {
+                let b = &buttons[0];
+                let max_multiplier = (0..n_counters)
+                    .map(|k| if b[k] > target[k] { 0 }
+                         else if b[k] > 0 { target[k] }
+                         else { usize::MAX })
+                    .min().unwrap();
+                let max_multiplier =
+                    min_sol(Some(max_multiplier), max_presses).unwrap();
+                assert!(max_multiplier < usize::MAX);
+
+                let mut sub_target: Vec<usize> = vec![0; n_counters];
+                let mut sub_best = None;
+                for m in (0..=max_multiplier).rev() {
+                    for k in 0..n_counters {
+                        sub_target[k] = target[k] - m*b[k];
+                    }
+                    let sub_r = subsolve(&buttons[1..], &sub_target,
+                                         min_sol(max_presses, sub_best),
+                                         cache);
+                    sub_best = min_sol(sub_best,
+                                       add_sol(Some(m), sub_r));
                 }
-                let sub_r = subsolve(&buttons[1..], &sub_target,
-                                     min_sol(max_presses, sub_best),
-                                     cache);
-                sub_best = min_sol(sub_best,
-                                   add_sol(Some(m), sub_r));
-            }
-
-            sub_best
-        };
-
-    // println!("{:?} <-- {:?} {:?}",
-    //          r, n_buttons, target);
-    cache.put(key, r);
-    r
-}
 
-fn solve_joltage(machine: &Machine) -> usize {
-    // strategy: dynamic programming, like with the coin change
-    // problem, but in multiple dimensions (one per joltage level)
-    let mut cache = LruCache::new(
-        std::num::NonZeroUsize::new(10_000_000).unwrap());
-    let n_counters = machine.joltage.len();
-    let mut buttons_joltage: Vec<Vec<usize>> = machine.buttons
-        .iter()
-        .map(|b| (0..n_counters)
-             .map(|k| (b >> k) & 1)
-            .collect())
-        .collect();
-    //WIP: sort buttons by rarity, i.e. count how many buttons update
-    // each level -- didn't help much :-/
-    let _counter_pop: Vec<usize> =
-        (0..n_counters)
-        .map(|k| buttons_joltage
-             .iter()
-             .map(|b| b[k])
-             .sum())
-        .collect();
-    buttons_joltage.sort_by_key(|b| {
-        // let v: Vec<usize> = (0..n_counters)
-        //     .map(|k| b[k]*(n_counters - counter_pop[k]))
-        //     .sorted()
-        //     .rev()
-        //     .collect();
-        let v: usize = b.iter().sum();
-        std::cmp::Reverse(v)
-    });
-    println!("{:?}", buttons_joltage);
-    subsolve(&buttons_joltage,
-             &machine.joltage,
-             Some(machine.joltage.iter().sum()), //helpful?
-             &mut cache)
-        .unwrap()
-}
+                sub_best
+            };
 
-pub fn parse_2(text: &str) -> i64 {
-    let machines = parse_text(text);
-    machines
-        .iter()
-        .map(solve_joltage)
-        .inspect(|r| { println!("{}", r)})
-        .sum::<usize>() as i64
-}
+        trace!("{:?} <-- {:?} {:?}", r, n_buttons, target);
+        cache.put(key, r);
+        r
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn solve_joltage_dp(machine: &Machine) -> usize {
+        // strategy: dynamic programming, like with the coin change
+        // problem, but in multiple dimensions (one per joltage level)
+        let mut cache = LruCache::new(
+            std::num::NonZeroUsize::new(10_000_000).unwrap());
+        let n_counters = machine.joltage.len();
+        let mut buttons_joltage: Vec<Vec<usize>> = machine.buttons
+            .iter()
+            .map(|b| (0..n_counters)
+                 .map(|k| (b >> k) & 1)
+                .collect())
+            .collect();
+        //WIP: sort buttons by rarity, i.e. count how many buttons update
+        // each level -- didn't help much :-/
+        let _counter_pop: Vec<usize> =
+            (0..n_counters)
+            .map(|k| buttons_joltage
+                 .iter()
+                 .map(|b| b[k])
+                 .sum())
+            .collect();
+        buttons_joltage.sort_by_key(|b| {
+            // let v: Vec<usize> = (0..n_counters)
+            //     .map(|k| b[k]*(n_counters - counter_pop[k]))
+            //     .sorted()
+            //     .rev()
+            //     .collect();
+            let v: usize = b.iter().sum();
+            std::cmp::Reverse(v)
+        });
+        debug!("buttons: {:?}", buttons_joltage);
+        subsolve(&buttons_joltage,
+                 &machine.joltage,
+                 Some(machine.joltage.iter().sum()), //helpful?
+                 &mut cache)
+            .unwrap()
+    }
 
     const INPUT_TEXT_1: &str = "
 [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
@@ -211,10 +209,38 @@ mod tests {
 ";
     #[test]
     fn test10_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 7);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(7)));
     }
     #[test]
     fn test10_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 10+12+11);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(10+12+11)));
+    }
+    #[test]
+    fn test10_joltage_dp() {
+        for machine in parse_text(INPUT_TEXT_1).unwrap() {
+            assert_eq!(solve_joltage(&machine), Ok(solve_joltage_dp(&machine)));
+        }
+    }
+    #[test]
+    fn test10_many_buttons() {
+        let machine = format!("[#.] {}(1) {{34,1}}\n", "(0) ".repeat(34));
+        assert_eq!(parse_1(&machine), Ok(Answer::from(1)));
+    }
+    #[test]
+    fn test10_errors() {
+        assert_eq!(parse_1("[.#] (0) (1,x) {1,2}\n"),
+                   Err(AocError::parse(1, 13, "x)", "expected number")));
+        assert_eq!(parse_1("[.#] (0) (1 {1,2}\n"),
+                   Err(AocError::parse(1, 12, "", "expected \")\"")));
+        assert_eq!(parse_1("[.#] (0) <1> {1,2}\n"),
+                   Err(AocError::parse(1, 10, "<1>", "expected \"{\"")));
+        assert_eq!(parse_1("[.#] (0,99) {1,2}\n"),
+                   Err(AocError::parse(1, 7, "0,99", "light index out of range")));
+        let levels: Vec<String> = (1..=70).map(|k| k.to_string()).collect();
+        let levels = levels.join(",");
+        assert_eq!(parse_2(&format!("[.#] (0) {{{}}}\n", levels)),
+                   Err(AocError::parse(1, 11, &levels, "too many joltage levels")));
+        assert!(matches!(parse_2("[.#] (0) {1,2}\n"), Err(AocError::Input(_))));
+        assert!(matches!(parse_1("[.#] (0) {1,2}\n"), Err(AocError::Input(_))));
     }
 }
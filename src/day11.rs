@@ -1,104 +1,60 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
-
-type Graph<'a> = HashMap<&'a str, Vec<&'a str>>;
-
-fn parse_text(text: &str) -> Graph<'_> {
-    let mut graph = HashMap::new();
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .for_each(|line| {
-            let (from, words) = line.split_once(": ").unwrap();
-            let to = words.split(" ").collect_vec();
-            graph.insert(from, to);
-        });
-    graph
-}
-
-fn count_paths(graph: &Graph,
-               label_source: &str, label_sink: &str) -> i64 {
-    let mut counts: HashMap<&str, i64> = HashMap::new();
-    let mut nodes_from: Vec<&str> = vec![label_source];
-
-    while !nodes_from.is_empty() {
-        let mut nodes_to: Vec<&str> = vec![];
-
-        nodes_from.iter().for_each(|label_from| {
-            graph.get(label_from).unwrap().iter()
-                .for_each(|label_to| {
-                    if let Some(c) = counts.get_mut(label_to) {
-                        *c += 1;
-                    } else {
-                        counts.insert(label_to, 1);
-                    }
-                    if *label_to != label_sink {
-                        nodes_to.push(label_to);
-                    }
-                })
-        });
-        nodes_from = nodes_to;
+use std::collections::HashSet;
+
+use crate::answer::Answer;
+use crate::error::{numbered_lines, AocError, Result};
+use crate::graph::Graph;
+use crate::log::debug;
+use crate::parser::{label, labelled, many0, parse_line, ws};
+use crate::solver::{DaySolver, Params};
+
+pub const SOLVER: DaySolver = DaySolver::new("day11", 11, &[parse_1, parse_2])
+    .with_params(&["from", "to", "via", "avoid"], &[query_1, query_2]);
+
+fn parse_text(text: &str) -> Result<Graph<'_>> {
+    let mut graph = Graph::new();
+    let mut devices = HashSet::new();
+    for (n, line) in numbered_lines(text) {
+        // e.g. you: bbb ccc
+        let (from, to) = parse_line(n, line, labelled(label(), many0(ws(label()))))?;
+        if !devices.insert(from) {
+            return Err(AocError::at(n, line, from, "duplicate device"));
+        }
+        graph.node(from);
+        for label_to in to {
+            graph.add_edge(from, label_to);
+        }
     }
-
-    *counts.get(label_sink).unwrap()
+    Ok(graph)
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let graph = parse_text(text);
-    count_paths(&graph, "you", "out")
+// paths between devices, where --from, --to, --via (devices to visit
+// in any order) and --avoid override the defaults of each part
+fn count_query(text: &str, params: &Params, from: &str, to: &str, via: &[&str])
+               -> Result<Answer> {
+    let graph = parse_text(text)?;
+    let from = params.get("from").unwrap_or(from);
+    let to = params.get("to").unwrap_or(to);
+    let via = params.list("via").unwrap_or(via.to_vec());
+    let avoid = params.list("avoid").unwrap_or_default();
+    let count = graph.count_paths_via(from, to, &via, &avoid)?;
+    debug!("paths {} -> {} via {:?} avoiding {:?}: {}", from, to, via, avoid, count);
+    Ok(count.into())
 }
 
-// same base logic as part 1, different map-based implementation to
-// accomodate nastier graph in part 2
-
-struct CountMap {
-    h: HashMap<String, i64>,
+fn query_1(text: &str, params: &Params) -> Result<Answer> {
+    count_query(text, params, "you", "out", &[])
 }
 
-impl CountMap {
-    fn new() -> CountMap {
-        CountMap{h: Default::default()}
-    }
-    fn inc(&mut self, label: &str, n: i64) {
-        if let Some(c) = self.h.get_mut(label) {
-            *c += n;
-        } else {
-            self.h.insert(label.to_string(), n);
-        }
-    }
+fn query_2(text: &str, params: &Params) -> Result<Answer> {
+    count_query(text, params, "svr", "out", &["dac", "fft"])
 }
 
-fn count_paths2(graph: &Graph,
-               label_source: &str, label_sink: &str) -> i64 {
-    let mut counts = CountMap::new();
-    let mut nodes_from = CountMap::new();
-
-    nodes_from.inc(label_source, 1);
-
-    while !nodes_from.h.is_empty() {
-        let mut nodes_to = CountMap::new();
-
-        nodes_from.h.iter().for_each(|(label_from, &n)| {
-            if let Some(out) = graph.get(&label_from[..]) {
-                out.iter().for_each(|&label_to| {
-                    counts.inc(label_to, n);
-                    if label_to != label_sink {
-                        nodes_to.inc(label_to, n);
-                    }
-                })
-            }
-        });
-        nodes_from = nodes_to;
-    }
-
-    *counts.h.get(label_sink).unwrap()
+pub fn parse_1(text: &str) -> Result<Answer> {
+    query_1(text, &Params::new())
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let graph = parse_text(text);
-    count_paths2(&graph, "svr", "fft")
-        * count_paths2(&graph, "fft", "dac")
-        * count_paths2(&graph, "dac", "out")
+pub fn parse_2(text: &str) -> Result<Answer> {
+    query_2(text, &Params::new())
 }
 
 #[cfg(test)]
@@ -119,13 +75,14 @@ iii: out
 ";
     #[test]
     fn test11_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 5);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(5)));
     }
 
     #[test]
     fn test11_count2() {
-        let graph = parse_text(INPUT_TEXT_1);
-        assert_eq!(count_paths2(&graph, "you", "out"), 5);
+        let graph = parse_text(INPUT_TEXT_1).unwrap();
+        assert_eq!(graph.count_paths("you", "out"), Ok(5));
+        assert_eq!(graph.count_paths("ccc", "out"), Ok(3));
     }
 
     const INPUT_TEXT_2: &str = "
@@ -145,6 +102,34 @@ hhh: out
 ";
     #[test]
     fn test11_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_2), 2);
+        assert_eq!(parse_2(INPUT_TEXT_2), Ok(Answer::from(2)));
+    }
+
+    #[test]
+    fn test11_query() {
+        let mut params = Params::new();
+        params.set("via", "fft");
+        assert_eq!(query_2(INPUT_TEXT_2, &params), Ok(Answer::from(4)));
+        params.set("avoid", "eee");
+        assert_eq!(query_2(INPUT_TEXT_2, &params), Ok(Answer::from(2)));
+        params.set("from", "bbb");
+        assert_eq!(query_2(INPUT_TEXT_2, &params), Ok(Answer::from(0)));
+
+        let mut params = Params::new();
+        params.set("from", "hhh");
+        params.set("via", "ccc");
+        assert_eq!(query_1(INPUT_TEXT_1, &params), Ok(Answer::from(3)));
+    }
+    #[test]
+    fn test11_errors() {
+        assert_eq!(parse_1("you: out\nbbb out\n"),
+                   Err(AocError::parse(2, 4, "", "expected \":\"")));
+        assert_eq!(parse_1("you: out\nbbb: o-t\n"),
+                   Err(AocError::parse(2, 7, "-t", "unexpected text")));
+        assert_eq!(parse_1("you: aaa\nyou: out\n"),
+                   Err(AocError::parse(2, 1, "you", "duplicate device")));
+        assert_eq!(parse_1("you: aaa\naaa: bbb out\nbbb: aaa\n"),
+                   Err(AocError::Input("cycle: aaa -> bbb -> aaa".to_string())));
+        assert!(matches!(parse_1("aaa: out\n"), Err(AocError::Input(_))));
     }
 }
@@ -1,4 +1,14 @@
-use regex::Regex;
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::log::{debug, trace};
+use crate::packing::{self, Piece};
+use crate::par;
+use crate::parser::{labelled, many1, parse_line, sections, separated_pair, tag, take_while1,
+                    terminated, uint, verify, ws};
+use crate::shape::Shape;
+use crate::solver::DaySolver;
+
+pub const SOLVER: DaySolver = DaySolver::new("day12", 12, &[parse_1]);
 
 struct Goal {
     x: usize,
@@ -6,74 +16,109 @@ struct Goal {
     reqs: Vec<usize>,
 }
 
-fn parse_text(text: &str) -> (Vec<Vec<String>>, Vec<Goal>) {
-    let re_shape_num = Regex::new(r"^(\d+):$").unwrap();
-    let re_shape_line = Regex::new(r"(^[\.\#]+)").unwrap();
-    let re_space_goals = Regex::new(r"(\d+)x(\d+): (.*)").unwrap();
-
-    let mut shapes: Vec<Vec<String>> = vec![];
+// shapes, e.g. "0:" followed by rows like "##.", then goals,
+// e.g. "12x5: 1 0 1 0 2 2", in sections separated by blank lines
+fn parse_text(text: &str) -> Result<(Vec<Shape>, Vec<Goal>)> {
+    let mut shapes: Vec<Shape> = vec![];
     let mut goals: Vec<Goal> = vec![];
 
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .for_each(|line| {
-            if let Some(_) = re_shape_num.captures(line) {
-                shapes.push(vec![]);
-            } else if let Some(caps) = re_shape_line.captures(line) {
-                let last_idx = shapes.len()-1;
-                let last_shape = &mut shapes[last_idx];
-                last_shape.push(caps[1].to_string());
-            } else if let Some(caps) = re_space_goals.captures(line) {
-                let x: usize = caps[1].parse().unwrap();
-                let y: usize = caps[2].parse().unwrap();
-                let reqs: Vec<usize> =
-                    caps[3]
-                    .split(" ")
-                    .map(|s| s.parse::<usize>().unwrap())
-                    .collect();
-                goals.push(Goal{ x, y, reqs })
-            }
-        });
-
-    (shapes, goals)
+    for section in sections(text) {
+        let (n, line) = section[0];
+        if parse_line(n, line, terminated(uint::<usize>(), tag(":"))).is_ok() {
+            let row = take_while1(|c| c == '.' || c == '#', "shape row");
+            let rows = section[1..].iter()
+                .map(|&(n, line)| parse_line(n, line, &row))
+                .collect::<Result<Vec<_>>>()?;
+            shapes.push(Shape::parse(&rows));
+            continue
+        }
+        let reqs = verify(many1(ws(uint())),
+                          |reqs: &Vec<usize>| reqs.len() <= shapes.len(),
+                          "more requirements than shapes");
+        let goal = labelled(separated_pair(uint(), tag("x"), uint()), reqs);
+        for &(n, line) in &section {
+            let ((x, y), reqs) = parse_line(n, line, &goal)?;
+            goals.push(Goal{ x, y, reqs })
+        }
+    }
+
+    Ok((shapes, goals))
+}
+
+fn fits(pieces: &[Piece], goal: &Goal) -> Result<bool> {
+    // goals may list fewer counts than there are shapes
+    let mut counts = goal.reqs.clone();
+    counts.resize(pieces.len(), 0);
+    let r = packing::fits(pieces, goal.x, goal.y, &counts)?;
+    debug!("{} {}x{} {:?}", if r { "V" } else { "." }, goal.x, goal.y, goal.reqs);
+    Ok(r)
+}
+
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let (shapes, goals) = parse_text(text)?;
+    for (k, shape) in shapes.iter().enumerate() {
+        trace!("shape {}, {} orientations:\n{}", k, shape.orientations().len(), shape);
+    }
+    let pieces: Vec<Piece> = shapes.iter().map(Piece::new).collect();
+
+    Ok(par::map(&goals, |goal| fits(&pieces, goal))
+        .into_iter()
+        .collect::<Result<Vec<bool>>>()?
+        .into_iter()
+        .filter(|&r| r)
+        .count()
+        .into())
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let (shapes, goals) = parse_text(text);
-
-    // reddit tells us that simply checking for total available space
-    // is enough... on real input only, not test input #facepalm
-
-    let shape_space: Vec<usize> =
-        shapes
-        .iter()
-        .map(|shape| {
-            shape
-                .iter()
-                .map(|line| line
-                     .as_bytes()
-                     .iter()
-                     .filter(|&c| *c == b'#')
-                     .count())
-                .sum()
-        })
-        .collect();
-
-    goals.iter().map(|goal| {
-        let avail = goal.x * goal.y;
-        let needed =
-            goal
-            .reqs
-            .iter()
-            .enumerate()
-            .map(|(i, count)| shape_space[i]*count)
-            .sum();
-        (avail, needed)
-    })
-        .inspect(|(avail, needed)|
-                 println!("{} avail {} needed {}",
-                          if avail >= needed {"V"} else {"."},
-                          avail, needed))
-        .filter(|(avail, needed)| avail >= needed)
-        .count() as i64
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AocError;
+
+    const INPUT_TEXT_1: &str = "
+0:
+###
+##.
+##.
+
+1:
+###
+##.
+.##
+
+2:
+.##
+###
+##.
+
+3:
+##.
+###
+##.
+
+4:
+###
+#..
+###
+
+5:
+###
+.#.
+###
+
+4x4: 0 0 0 0 2 0
+12x5: 1 0 1 0 2 2
+12x5: 1 0 1 0 3 2
+";
+    #[test]
+    fn test12_parse1() {
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(2)));
+    }
+    #[test]
+    fn test12_errors() {
+        assert_eq!(parse_1("0:\n#.\n\n4x4: 1 2\n"),
+                   Err(AocError::parse(4, 6, "1 2", "more requirements than shapes")));
+        assert_eq!(parse_1("0:\n#x\n"),
+                   Err(AocError::parse(2, 2, "x", "unexpected text")));
+    }
 }
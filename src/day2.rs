@@ -1,21 +1,19 @@
-use regex::Regex;
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::log::debug;
+use crate::par;
+use crate::parser::{many1, opt, parse_lines, range, tag, terminated, uint};
+use crate::solver::DaySolver;
 
-fn parse_text(text: &str) -> Vec<(i64, i64)> {
-    let re = Regex::new(r"(\d+)-(\d+)").unwrap();
+pub const SOLVER: DaySolver = DaySolver::new("day2", 2, &[parse_1, parse_2]);
 
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .flat_map(|line| line.split(","))
-        .map(|seqstr| {
-            let caps = re.captures(seqstr).unwrap();
-            let lo = caps[1].parse().unwrap();
-            let hi = caps[2].parse().unwrap();
-            (lo, hi)
-        })
-        .collect()
+fn parse_text(text: &str) -> Result<Vec<(u128, u128)>> {
+    // e.g. 11-22,95-115 with an optional trailing comma
+    let ranges = many1(terminated(range(uint()), opt(tag(","))));
+    Ok(parse_lines(text, ranges)?.into_iter().flatten().collect())
 }
 
-fn count_digits(mut n: i64) -> u32 {
+fn count_digits(mut n: u128) -> u32 {
     let mut d = 1;
     while n > 9 {
         d += 1;
@@ -24,89 +22,95 @@ fn count_digits(mut n: i64) -> u32 {
     d
 }
 
-fn sum_inval(lo: i64, hi: i64) -> i64 {
-    assert!(lo < hi);
-
-    let lo_digits: u32 = count_digits(lo);
-    let hi_digits: u32 = count_digits(hi);
+fn overflow() -> AocError {
+    AocError::Input("sum of invalid ids too large".to_string())
+}
 
-    // don't handle this extra case unless required
-    assert!(lo_digits + 1 >= hi_digits);
+// lo..=hi split into ranges with the same number of digits:
+// (digits, from, to)
+fn digit_ranges(lo: u128, hi: u128) -> Result<Vec<(u32, u128, u128)>> {
+    if lo > hi {
+        return Err(AocError::Input(format!("range {}-{} starts after its end", lo, hi)));
+    }
+    Ok((count_digits(lo)..=count_digits(hi))
+       .map(|digits| (digits,
+                      lo.max(10u128.pow(digits - 1)),
+                      10u128.checked_pow(digits).map_or(hi, |p| hi.min(p - 1))))
+       .collect())
+}
 
-    if lo_digits < hi_digits {
-        // split interval into ranges with same # of digits
-        sum_inval(lo, 10i64.pow(lo_digits)-1)
-            + sum_inval(10i64.pow(lo_digits), hi)
-    } else if !lo_digits.is_multiple_of(2) {
-        // odd number of digits can't be invalid
-        0
-    } else {
-        // split lo and hi into top/bottom pair of numbers
-        // with equal number of digits
-        let mid_pow = 10i64.pow(lo_digits / 2);
-        let top_lo = lo / mid_pow;
-        let top_hi = hi / mid_pow;
-        let bot_lo = lo % mid_pow;
-        let bot_hi = hi % mid_pow;
+// sum of the ids in from..=to, which all have the given number of
+// digits, made of a block of digits repeated: these are block * mult,
+// with mult = 1 + 10^len + 10^2len + .. and block of len digits
+fn sum_repeats(from: u128, to: u128, digits: u32, len: u32) -> Result<u128> {
+    let mult: u128 = (0..digits / len).map(|k| 10u128.pow(k * len)).sum();
+    let top_lo = from.div_ceil(mult);
+    let top_hi = to / mult;
+    if top_lo > top_hi {
+        return Ok(0)
+    }
+    // mult * (top_lo + .. + top_hi), halving whichever factor is even
+    let (n, s) = (top_hi - top_lo + 1, top_lo + top_hi);
+    let (n, s) = if n.is_multiple_of(2) { (n / 2, s) } else { (n, s / 2) };
+    n.checked_mul(s)
+        .and_then(|t| t.checked_mul(mult))
+        .ok_or_else(overflow)
+}
 
-        let mut invals = 0;
-        if top_lo == top_hi {
-            // same top digits: only inval id has the same as its
-            // bottom digits
-            if bot_lo <= top_lo
-                && top_lo <= bot_hi {
-                    invals += top_lo * mid_pow + top_lo
-                }
-        } else {
-            if bot_lo <= top_lo {
-                invals += top_lo * mid_pow + top_lo
-            }
-            invals += (top_lo+1 .. top_hi)
-                .map(|top| top * mid_pow + top)
-                .sum::<i64>();
-            if top_hi <= bot_hi {
-                invals += top_hi * mid_pow + top_hi
-            }
+// sum of the ids in lo..=hi made of some digits repeated twice, or an
+// error if it does not fit in a u128
+fn sum_inval(lo: u128, hi: u128) -> Result<u128> {
+    let mut invals: u128 = 0;
+    // odd numbers of digits can't be invalid
+    for (digits, from, to) in digit_ranges(lo, hi)? {
+        if digits.is_multiple_of(2) {
+            invals = invals.checked_add(sum_repeats(from, to, digits, digits / 2)?)
+                .ok_or_else(overflow)?;
         }
-        invals
     }
+    Ok(invals)
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    parse_text(text)
-        .into_iter()
-        .map(|(lo, hi)| sum_inval(lo, hi))
-        .sum()
+fn checked_sum(sums: Vec<Result<u128>>) -> Result<u128> {
+    sums.into_iter()
+        .inspect(|s| if let Ok(s) = s { debug!("sum of invalid ids: {}", s) })
+        .try_fold(0u128, |acc, s| acc.checked_add(s?).ok_or_else(overflow))
 }
 
-fn is_inval2(n: &i64) -> bool {
-    let s = n.to_string();
-    let s_len = s.len();
-    for prefix_len in 1..s_len/2+1 {
-        if s_len.is_multiple_of(prefix_len) {
-            let prefix = &s[0..prefix_len];
-            let reps = s_len / prefix_len;
-            let is_repeat = (0..reps)
-                .all(|k| prefix == &s[k*prefix_len..(k+1)*prefix_len]);
-            if is_repeat {
-                return true
-            }
-        }
-    }
-    false
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let ranges = parse_text(text)?;
+    Ok(checked_sum(par::map(&ranges, |&(lo, hi)| sum_inval(lo, hi)))?.into())
 }
 
-fn sum_inval2(lo: i64, hi: i64) -> i64 {
-    (lo..hi+1)
-        .filter(is_inval2)
-        .sum()
+// sum of the ids in lo..=hi made of some digits repeated at least
+// twice. An id repeating a block of len digits also repeats blocks of
+// every multiple of its shortest block length, so sums by block length
+// overlap: exact[len], the sum of ids whose shortest block has len
+// digits, is the sum by block length len minus exact[d] for every
+// smaller divisor d of len.
+fn sum_inval2(lo: u128, hi: u128) -> Result<u128> {
+    let mut invals: u128 = 0;
+    for (digits, from, to) in digit_ranges(lo, hi)? {
+        let lens: Vec<u32> = (1..digits).filter(|&len| digits.is_multiple_of(len)).collect();
+        let mut exact: Vec<u128> = vec![];
+        for (k, &len) in lens.iter().enumerate() {
+            let shorter: u128 = lens[..k].iter()
+                .zip(&exact)
+                .filter(|&(&d, _)| len.is_multiple_of(d))
+                .map(|(_, &e)| e)
+                .sum();
+            exact.push(sum_repeats(from, to, digits, len)? - shorter);
+        }
+        invals = exact.into_iter()
+            .try_fold(invals, |acc, e| acc.checked_add(e))
+            .ok_or_else(overflow)?;
+    }
+    Ok(invals)
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    parse_text(text)
-        .into_iter()
-        .map(|(lo, hi)| sum_inval2(lo, hi))
-        .sum()
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let ranges = parse_text(text)?;
+    Ok(checked_sum(par::map(&ranges, |&(lo, hi)| sum_inval2(lo, hi)))?.into())
 }
 
 #[cfg(test)]
@@ -118,17 +122,20 @@ mod tests {
 ";
     #[test]
     fn test_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 1227775554);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(1227775554)));
     }
     #[test]
     fn test_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 4174379265);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(4174379265i64)));
     }
 
     #[test]
     fn test_parse_text() {
-        assert_eq!(parse_text("\n123-456,7890-1234\n"),
+        assert_eq!(parse_text("\n123-456,7890-1234\n").unwrap(),
                               [(123, 456), (7890, 1234)]);
+        assert_eq!(parse_text("12-34,\n").unwrap(), [(12, 34)]);
+        assert_eq!(parse_text("\n123-456,78x0-1234\n"),
+                   Err(AocError::parse(2, 11, "x0-1234", "expected \"-\"")));
     }
     #[test]
     fn test_count_digits() {
@@ -139,11 +146,61 @@ mod tests {
     }
     #[test]
     fn test_count_inval() {
-        assert_eq!(sum_inval(11, 22), 11+22);
+        assert_eq!(sum_inval(11, 22), Ok(11+22));
+        // single values, and empty ranges
+        assert_eq!(sum_inval(1212, 1212), Ok(1212));
+        assert_eq!(sum_inval(1213, 1213), Ok(0));
+        assert!(matches!(sum_inval(22, 11), Err(AocError::Input(_))));
+        // across several digit lengths
+        assert_eq!(sum_inval(5, 1111), Ok((1..=9).map(|t| t * 11).sum::<u128>() + 1010 + 1111));
+        for (lo, hi) in [(1, 1), (1, 12345), (95, 115), (998, 100_000), (123_123, 9_999_999)] {
+            assert_eq!(sum_inval(lo, hi), Ok((lo..=hi).filter(|&n| {
+                let s = n.to_string();
+                s.len() % 2 == 0 && s[..s.len() / 2] == s[s.len() / 2..]
+            }).sum::<u128>()), "{}-{}", lo, hi);
+        }
     }
+    #[test]
+    fn test_count_inval_large() {
+        let top = 10u128.pow(14) + 7;
+        let id = top * (10u128.pow(15) + 1);
+        assert_eq!(sum_inval(id - 5, id + 5), Ok(id));
+        assert_eq!(sum_inval(10u128.pow(30) - 10u128.pow(15), 10u128.pow(31)),
+                   Ok(999_999_999_999_999 * (10u128.pow(15) + 1)));
+        assert_eq!(sum_inval(10u128.pow(38), u128::MAX), Ok(0));
+        assert!(sum_inval(1, u128::MAX).is_err());
+        assert_eq!(parse_1(&format!("{}-{}", id, id)), Ok(Answer::from(id)));
+    }
+    // reference: some prefix repeated over the whole id
+    fn is_inval2(n: &u128) -> bool {
+        let s = n.to_string();
+        (1..=s.len() / 2)
+            .filter(|&len| s.len().is_multiple_of(len))
+            .any(|len| s.as_bytes().chunks(len).all(|c| c == &s.as_bytes()[..len]))
+    }
+
     #[test]
     fn test_count_inval2() {
-        assert_eq!(sum_inval2(11, 22), 11+22);
-        assert_eq!(sum_inval2(95, 115), 99+111);
+        assert_eq!(sum_inval2(11, 22), Ok(11+22));
+        assert_eq!(sum_inval2(95, 115), Ok(99+111));
+        assert_eq!(sum_inval2(111_111, 111_111), Ok(111_111));
+        assert!(matches!(sum_inval2(22, 11), Err(AocError::Input(_))));
+        for (lo, hi) in [(1, 1), (1, 1_000_000), (95, 115), (998, 100_000), (123_123, 9_999_999)] {
+            assert_eq!(sum_inval2(lo, hi), Ok((lo..=hi).filter(is_inval2).sum::<u128>()),
+                       "{}-{}", lo, hi);
+        }
+    }
+    #[test]
+    fn test_count_inval2_large() {
+        // every id of 12 digits with a repeated block: blocks of 1, 2,
+        // 3, 4 or 6 digits, less the overlaps
+        let id = 123_123_123_123u128;
+        assert_eq!(sum_inval2(id - 5, id + 5), Ok(id));
+        let all = sum_inval2(10u128.pow(11), 10u128.pow(12) - 1).unwrap();
+        let blocks = |len: u32| sum_repeats(10u128.pow(11), 10u128.pow(12) - 1, 12, len).unwrap();
+        assert_eq!(all, blocks(6) + blocks(4) - blocks(2));
+        // wide ranges are as quick as narrow ones
+        assert!(sum_inval2(1, 10u128.pow(20)).unwrap() > sum_inval(1, 10u128.pow(20)).unwrap());
+        assert!(sum_inval2(1, u128::MAX).is_err());
     }
 }
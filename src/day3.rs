@@ -1,18 +1,33 @@
 use std::cmp;
 use std::collections::HashMap;
+use crate::answer::Answer;
+use crate::error::{numbered_lines, AocError, Result};
+use crate::log::debug;
+use crate::par;
+use crate::solver::DaySolver;
 
-fn parse_text(text: &str) -> Vec<Vec<u8>> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line
-             .as_bytes()
-             .iter()
-             .map(|b| b - b'0')
-            .collect())
+pub const SOLVER: DaySolver = DaySolver::new("day3", 3, &[parse_1, parse_2]);
+
+fn parse_text(text: &str) -> Result<Vec<Vec<u8>>> {
+    numbered_lines(text)
+        .map(|(n, line)| {
+            let bank = line.trim_end();
+            if let Some(k) = bank.find(|c: char| !c.is_ascii_digit()) {
+                return Err(AocError::at(n, line, &bank[k..k+1], "expected digit"));
+            }
+            if bank.len() < 2 {
+                return Err(AocError::at(n, line, bank, "bank needs at least 2 batteries"));
+            }
+            Ok(bank
+               .as_bytes()
+               .iter()
+               .map(|b| b - b'0')
+               .collect())
+        })
         .collect()
 }
 
-fn joltage(bank: Vec<u8>) -> i64 {
+fn joltage(bank: &[u8]) -> i64 {
     // make array of max subsequent digit
     let max_post: Vec<u8> = bank
         .iter()
@@ -32,11 +47,13 @@ fn joltage(bank: Vec<u8>) -> i64 {
         .max().unwrap()
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    parse_text(text)
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let banks = parse_text(text)?;
+    Ok(par::map(&banks, |bank| joltage(bank))
         .into_iter()
-        .map(joltage)
-        .sum()
+        .inspect(|j| debug!("bank joltage {}", j))
+        .sum::<i64>()
+        .into())
 }
 
 // joltage2 requires memoization, its cache is indexed by:
@@ -66,11 +83,13 @@ fn joltage2(bank: &[u8], digits: u32, cache: &mut Cache) -> i64 {
     }
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    parse_text(text)
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let banks = parse_text(text)?;
+    Ok(par::map(&banks, |bank| joltage2(bank, 12, &mut HashMap::new()))
         .into_iter()
-        .map(|bank| joltage2(&bank, 12, &mut HashMap::new()))
-        .sum()
+        .inspect(|j| debug!("bank joltage {}", j))
+        .sum::<i64>()
+        .into())
 }
 
 #[cfg(test)]
@@ -85,10 +104,17 @@ mod tests {
 ";
     #[test]
     fn test3_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 357);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(357)));
     }
     #[test]
     fn test3_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 3121910778619);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(3121910778619i64)));
+    }
+    #[test]
+    fn test3_errors() {
+        assert_eq!(parse_1("12\n3a4\n"),
+                   Err(AocError::parse(2, 2, "a", "expected digit")));
+        assert_eq!(parse_1("7\n"),
+                   Err(AocError::parse(1, 1, "7", "bank needs at least 2 batteries")));
     }
 }
@@ -1,52 +1,47 @@
-use itertools::iproduct;
+use crate::answer::Answer;
+use crate::error::Result;
+use crate::grid::{Grid, Pos};
+use crate::log::debug;
+use crate::solver::DaySolver;
 
-fn parse_text(text: &str) -> Vec<Vec<bool>> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.chars()
-             .map(|c| c == '@')
-             .collect())
-        .collect()
-}
-
-fn neighbours_1d_unchecked(n: usize) -> Vec<usize> {
-    vec![n.overflowing_sub(1).0, n, n+1]
-}
+pub const SOLVER: DaySolver = DaySolver::new("day4", 4, &[parse_1, parse_2]);
 
-fn neighbours<T>(grid: &[Vec<T>], i: usize, j: usize) -> Vec<(usize, usize)> {
-    iproduct!(neighbours_1d_unchecked(i), neighbours_1d_unchecked(j))
-        .filter(|&(x, y)| x < grid.len() && y < grid[0].len())
-        .collect::<Vec<(usize, usize)>>()
+fn parse_text(text: &str) -> Result<Grid<bool>> {
+    Grid::parse(text, |c| match c {
+        '@' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
 }
 
-fn count_adjacent(grid: &[Vec<bool>], x: usize, y: usize) -> usize {
-    neighbours(grid, x, y)
-        .iter()
-        .filter(|(i, j)| (*i, *j) != (x, y) && grid[*i][*j])
+fn count_adjacent(grid: &Grid<bool>, pos: Pos) -> usize {
+    grid.neighbours8(pos)
+        .filter(|&p| grid[p])
         .count()
 }
 
-fn removables(grid: &[Vec<bool>]) -> Vec<(usize, usize)> {
-    iproduct!(0..grid.len(), 0..grid[0].len())
-        .filter(|&(x, y)| grid[x][y] && count_adjacent(grid, x, y) < 4)
+fn removables(grid: &Grid<bool>) -> Vec<Pos> {
+    grid.positions()
+        .filter(|&p| grid[p] && count_adjacent(grid, p) < 4)
         .collect()
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let grid = parse_text(text);
-    removables(&grid).len() as i64
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let grid = parse_text(text)?;
+    Ok(removables(&grid).len().into())
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let mut grid = parse_text(text);
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let mut grid = parse_text(text)?;
     let mut removed = 0;
 
     loop {
         let r = removables(&grid);
-        r.iter().for_each(|&(x, y)| grid[x][y] = false);
+        r.iter().for_each(|&p| grid[p] = false);
         removed += r.len();
+        debug!("removed {} rolls, {} total", r.len(), removed);
         if r.is_empty() {
-            return removed as i64;
+            return Ok(removed.into());
         }
     }
 }
@@ -54,6 +49,7 @@ pub fn parse_2(text: &str) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::AocError;
 
     const INPUT_TEXT_1: &str = "
 ..@@.@@@@.
@@ -69,10 +65,17 @@ mod tests {
 ";
     #[test]
     fn test4_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 13);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(13)));
     }
     #[test]
     fn test4_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 43);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(43)));
+    }
+    #[test]
+    fn test4_errors() {
+        assert_eq!(parse_1("..@\n.x.\n"),
+                   Err(AocError::parse(2, 2, "x", "unexpected character")));
+        assert_eq!(parse_1("..@\n.@\n"),
+                   Err(AocError::parse(2, 1, ".@", "lines must all have the same length")));
     }
 }
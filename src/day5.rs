@@ -1,5 +1,11 @@
-use regex::Regex;
 use std::cmp::Ordering;
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::log::{debug, trace};
+use crate::parser::{parse_line, range, sections, uint, verify};
+use crate::solver::DaySolver;
+
+pub const SOLVER: DaySolver = DaySolver::new("day5", 5, &[parse_1, parse_2]);
 
 struct Edge {
     pos: usize,
@@ -21,25 +27,28 @@ fn cmp_edge(a: &Edge, b: &Edge) -> std::cmp::Ordering {
     }
 }
 
-fn parse_text(text: &str) -> (Vec<Edge>, Vec<usize>) {
-    let re = Regex::new(r"(\d+)-(\d+)").unwrap();
+fn parse_text(text: &str) -> Result<(Vec<Edge>, Vec<usize>)> {
     let mut intervals: Vec<Edge> = vec![];
     let mut items: Vec<usize> = vec![];
 
-    text.lines().for_each(|line|{
-        if line.is_empty() {
-            return;
-        }
-        if let Some(caps) = re.captures(line) {
-            let a: usize = caps[1].parse().unwrap();
-            let b: usize = caps[2].parse().unwrap();
+    // fresh ranges, then an optional section of ingredient ids
+    let sections = sections(text);
+    if sections.len() > 2 {
+        return Err(AocError::Input("expected ranges, then ids".to_string()));
+    }
+    if let Some(ranges) = sections.first() {
+        for &(n, line) in ranges {
+            let (a, b) = parse_line(n, line, verify(range(uint()), |&(a, b)| a <= b,
+                                                    "range start after end"))?;
             intervals.push(Edge{pos: a, up: true, count: 0});
             intervals.push(Edge{pos: b, up: false, count: 0});
-        } else {
-            let p: usize = line.parse().unwrap();
-            items.push(p);
         }
-    });
+    }
+    if let Some(ids) = sections.get(1) {
+        for &(n, line) in ids {
+            items.push(parse_line(n, line, uint())?);
+        }
+    }
 
     intervals.sort_by(cmp_edge);
 
@@ -54,11 +63,12 @@ fn parse_text(text: &str) -> (Vec<Edge>, Vec<usize>) {
         }
     });
 
-    (intervals, items)
+    Ok((intervals, items))
 }
 
 fn is_fresh(intervals: &[Edge], item: usize) -> bool {
-    if item < intervals[0].pos
+    if intervals.is_empty()
+        || item < intervals[0].pos
         || item > intervals.last().unwrap().pos {
             return false;
         }
@@ -72,15 +82,17 @@ fn is_fresh(intervals: &[Edge], item: usize) -> bool {
     e.pos == item || !(e.up && e.count == 1)
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let (intervals, items) = parse_text(text);
-    items.iter()
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let (intervals, items) = parse_text(text)?;
+    debug!("{} interval edges, {} items", intervals.len(), items.len());
+    Ok(items.iter()
         .filter(|&&item| is_fresh(&intervals, item))
-        .count() as i64
+        .count()
+        .into())
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let (intervals, _) = parse_text(text);
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let (intervals, _) = parse_text(text)?;
 
     let mut total = 0;
     let mut range_start = 0;
@@ -89,16 +101,18 @@ pub fn parse_2(text: &str) -> i64 {
             if e.up {
                 range_start = e.pos
             } else {
+                trace!("merged range {}-{}", range_start, e.pos);
                 total += e.pos - range_start + 1
             }
         }
     });
-    total as i64
+    Ok(total.into())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::AocError;
 
     const INPUT_TEXT_1: &str = "
 3-5
@@ -115,10 +129,22 @@ mod tests {
 ";
     #[test]
     fn test5_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 3);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(3)));
     }
     #[test]
     fn test5_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 14);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(14)));
+    }
+    #[test]
+    fn test5_errors() {
+        assert_eq!(parse_1("3-5\n\n1-x\n"),
+                   Err(AocError::parse(3, 2, "-x", "unexpected text")));
+        assert_eq!(parse_1("3-5\n4\n\n1\n"),
+                   Err(AocError::parse(2, 2, "", "expected \"-\"")));
+        assert!(matches!(parse_1("3-5\n\n1\n\n2\n"), Err(AocError::Input(_))));
+        assert_eq!(parse_1("3-5\n5-3\n\n1\n"),
+                   Err(AocError::parse(2, 1, "5-3", "range start after end")));
+        assert_eq!(parse_2("3-5\n4-4\n"), Ok(Answer::from(3)));
+        assert_eq!(parse_2("3-5\n"), Ok(Answer::from(3)));
     }
 }
@@ -1,72 +1,95 @@
-use regex::Regex;
+use crate::answer::Answer;
+use crate::error::{numbered_lines, AocError, Result};
+use crate::log::debug;
+use crate::parser::{alt, many1, map, one_of, opt, pair, parse_line, parse_lines,
+                    uint, ws};
+use crate::solver::DaySolver;
 
-fn parse_text(text: &str) -> (Vec<Vec<i64>>, Vec<&str>) {
-    let re = Regex::new(r"\S+").unwrap();
+pub const SOLVER: DaySolver = DaySolver::new("day6", 6, &[parse_1, parse_2]);
+
+enum Row {
+    Nums(Vec<i64>),
+    Ops(Vec<char>),
+}
+
+fn parse_text(text: &str) -> Result<(Vec<Vec<i64>>, Vec<char>)> {
+    let row = alt(map(many1(ws(uint())), Row::Nums),
+                  map(many1(ws(one_of("+*"))), Row::Ops));
     let mut nums: Vec<Vec<i64>> = vec![];
-    let mut ops: Vec<&str> = vec![];
+    let mut ops: Vec<char> = vec![];
 
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| re.find_iter(line)
-             .map(|m| m.as_str())
-             .collect())
-        .for_each(|v: Vec<&str>| {
-            if v[0] == "+" || v[0] == "*" {
-                ops = v
-            } else {
-                nums.push(v.iter()
-                          .map(|s| s.parse::<i64>().unwrap())
-                          .collect())
-            }
-        });
+    for r in parse_lines(text, row)? {
+        match r {
+            Row::Nums(v) => nums.push(v),
+            Row::Ops(v) => ops = v,
+        }
+    }
+
+    if ops.is_empty() {
+        return Err(AocError::Input("missing line of operators".to_string()));
+    }
+    if nums.is_empty() || nums.iter().any(|row| row.len() != ops.len()) {
+        return Err(AocError::Input(
+            "every line must have one number per operator".to_string()));
+    }
 
-    (nums, ops)
+    Ok((nums, ops))
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let (nums, ops) = parse_text(text);
-    (0..ops.len())
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let (nums, ops) = parse_text(text)?;
+    Ok((0..ops.len())
         .map(|col| (0..nums.len())
              .map(|row| nums[row][col])
-             .reduce(|acc, e| if ops[col] == "*" { acc * e} else { acc + e })
+             .reduce(|acc, e| if ops[col] == '*' { acc * e} else { acc + e })
              .unwrap())
-        .sum()
+        .sum::<i64>()
+        .into())
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let re = Regex::new(r"(\d+)\s*([*+]?)").unwrap();
-    let grid: Vec<_> = text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.as_bytes())
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let (first_line, _) = numbered_lines(text).next()
+        .ok_or_else(|| AocError::Input("empty input".to_string()))?;
+    let grid: Vec<_> = numbered_lines(text)
+        .map(|(_, line)| line.as_bytes())
         .collect();
+    let width = grid.iter().map(|row| row.len()).max().unwrap();
 
     let mut total = 0;
     let mut args: Vec<i64> = vec![];
 
-    (0..grid[0].len())
-        .rev()
-        .for_each(|col| {
-            let v = (0..grid.len())
-                .map(|row| grid[row][col])
-                .collect::<Vec<u8>>();
-            let s = str::from_utf8(&v).unwrap();
+    for col in (0..width).rev() {
+        // short lines are padded with spaces
+        let v = (0..grid.len())
+            .map(|row| grid[row].get(col).copied().unwrap_or(b' '))
+            .collect::<Vec<u8>>();
+        let s = String::from_utf8_lossy(&v);
+        if s.trim().is_empty() {
+            continue
+        }
 
-            if let Some(caps) = re.captures(s) {
-                args.push(caps[1].parse().unwrap());
+        // a column holds one number, maybe followed by an operator;
+        // errors point at the column, read top to bottom
+        let column = pair(ws(uint::<i64>()), ws(opt(one_of("*+"))));
+        let (arg, op) = parse_line(first_line, &s, column).map_err(|e| match e {
+            AocError::Parse{ msg, .. } => AocError::parse(first_line, col+1, &s, &msg),
+            e => e,
+        })?;
+        args.push(arg);
 
-                if !caps[2].is_empty() {
-                    let inc = if &caps[2] == "*" {
-                        args.iter().product::<i64>()
-                    } else {
-                        args.iter().sum::<i64>()
-                    };
-                    total += inc;
-                    args.clear();
-                }
-            }
-        });
+        if let Some(op) = op {
+            let inc = if op == '*' {
+                args.iter().product::<i64>()
+            } else {
+                args.iter().sum::<i64>()
+            };
+            debug!("{} {:?} = {}", op, args, inc);
+            total += inc;
+            args.clear();
+        }
+    }
 
-    total
+    Ok(total.into())
 }
 
 #[cfg(test)]
@@ -81,10 +104,20 @@ mod tests {
 ";
     #[test]
     fn test6_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 4277556);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(4277556)));
     }
     #[test]
     fn test6_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 3263827);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(3263827)));
+    }
+    #[test]
+    fn test6_errors() {
+        assert_eq!(parse_1("1 2\n3 x\n* +\n"),
+                   Err(AocError::parse(2, 3, "x", "unexpected text")));
+        assert_eq!(parse_1("1 2\n* -\n"),
+                   Err(AocError::parse(2, 3, "-", "unexpected text")));
+        assert!(matches!(parse_1("1 2\n3\n* +\n"), Err(AocError::Input(_))));
+        assert_eq!(parse_2("1 2\n3 x\n* +\n"),
+                   Err(AocError::parse(1, 3, "2x+", "unexpected text")));
     }
 }
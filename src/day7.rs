@@ -1,26 +1,38 @@
-fn parse_text(text: &str) -> Vec<&[u8]> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.as_bytes())
-        .collect()
+use crate::answer::Answer;
+use crate::error::{numbered_lines, AocError, Result};
+use crate::grid::Grid;
+use crate::log::trace;
+use crate::solver::DaySolver;
+
+pub const SOLVER: DaySolver = DaySolver::new("day7", 7, &[parse_1, parse_2]);
+
+// grid of the manifold, and column of the start position
+fn parse_text(text: &str) -> Result<(Grid<char>, usize)> {
+    let grid = Grid::parse(text, |c| matches!(c, '.' | '^' | 'S').then_some(c))?;
+
+    let s_pos = grid.row(0).iter().position(|&c| c == 'S')
+        .ok_or_else(|| {
+            let (n, first) = numbered_lines(text).next().unwrap();
+            let first = first.trim_end();
+            AocError::at(n, first, first, "missing start position 'S'")
+        })?;
+    Ok((grid, s_pos))
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let grid = parse_text(text);
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let (grid, s_pos) = parse_text(text)?;
 
-    let width = grid[0].len();
+    let width = grid.width();
     let mut beams: Vec<bool> = vec![false; width];
-
-    let s_pos = grid[0].iter().position(|&c| c == b'S').unwrap();
     beams[s_pos] = true;
 
-    let mut split_count = 0;
-    for line in grid.iter() {
+    let mut split_count: usize = 0;
+    for line in grid.rows() {
         let mut next_beams: Vec<bool> = vec![false; width];
         for col in 0..width {
             let mut b = beams[col];
 
-            if line[col] == b'^' {
+            if line[col] == '^' {
                 if b {
                     split_count += 1;
                     if col > 0 {
@@ -35,26 +47,26 @@ pub fn parse_1(text: &str) -> i64 {
             next_beams[col] |= b;
         }
         beams = next_beams;
+        trace!("{} beams, {} splits", beams.iter().filter(|&&b| b).count(), split_count);
     }
 
-    split_count
+    Ok(split_count.into())
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let grid = parse_text(text);
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let (grid, s_pos) = parse_text(text)?;
 
-    let width = grid[0].len();
-    let mut tls: Vec<i64> = vec![0; width];
-
-    let s_pos = grid[0].iter().position(|&c| c == b'S').unwrap();
+    let width = grid.width();
+    // timeline counts grow exponentially with depth
+    let mut tls: Vec<u128> = vec![0; width];
     tls[s_pos] = 1;
 
-    for line in grid.iter() {
-        let mut next_tls: Vec<i64> = vec![0; width];
+    for line in grid.rows() {
+        let mut next_tls: Vec<u128> = vec![0; width];
         for col in 0..width {
             let mut tl = tls[col];
 
-            if line[col] == b'^' {
+            if line[col] == '^' {
                 if tl > 0 {
                     if col > 0 {
                         next_tls[col-1] += tl;
@@ -68,9 +80,10 @@ pub fn parse_2(text: &str) -> i64 {
             next_tls[col] += tl;
         }
         tls = next_tls;
+        trace!("{} timelines", tls.iter().sum::<u128>());
     }
 
-    tls.iter().sum()
+    Ok(tls.iter().sum::<u128>().into())
 }
 
 #[cfg(test)]
@@ -97,10 +110,19 @@ mod tests {
 ";
     #[test]
     fn test7_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 21);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(21)));
     }
     #[test]
     fn test7_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 40);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(40)));
+    }
+    #[test]
+    fn test7_errors() {
+        assert_eq!(parse_1("...\n.^.\n"),
+                   Err(AocError::parse(1, 1, "...", "missing start position 'S'")));
+        assert_eq!(parse_2(".S.\n.^\n"),
+                   Err(AocError::parse(2, 1, ".^", "lines must all have the same length")));
+        assert_eq!(parse_2(".S.\n.v.\n"),
+                   Err(AocError::parse(2, 2, "v", "unexpected character")));
     }
 }
@@ -1,107 +1,76 @@
-use itertools::Itertools;
-use std::collections::BinaryHeap;
 use std::cmp::{min, Reverse};
-
-type Point = (i64, i64, i64);
-
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-struct Pair {
-    dist: i64,
-    i: usize,
-    j: usize,
-}
-
-fn parse_text(text: &str) -> Vec<Point> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.split(",")
-             .map(|s| s.parse::<i64>().unwrap())
-             .collect_tuple() // from Itertools
-             .unwrap())
-        .collect()
-}
-
-fn distance(a: Point, b: Point) -> i64 {
-    (a.0 - b.0).pow(2) + (a.1 - b.1).pow(2) + (a.2 - b.2).pow(2)
-}
-
-fn dist_heap(points: &[Point]) -> BinaryHeap<Reverse<Pair>> {
-    let n = points.len();
-
-    // collect distances between every pair into min-heap
-    let mut heap = BinaryHeap::new();
-    for i in 0..n {
-        for j in i+1..n {
-            let dist = distance(points[i], points[j]);
-            let p = Pair{dist, i, j};
-            heap.push(Reverse(p));
-        }
-    }
-
-    heap
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::log::{debug, trace};
+use crate::parser::{int, map, parse_lines, separated_pair, tag, verify};
+use crate::solver::DaySolver;
+use crate::spatial::{KdTree, Point};
+use crate::unionfind::UnionFind;
+
+pub const SOLVER: DaySolver = DaySolver::new("day8", 8, &[parse_1, parse_2]);
+
+// largest coordinate, so that squared distances fit in an i64
+const MAX_COORD: u64 = 1 << 29;
+
+fn parse_text(text: &str) -> Result<Vec<Point>> {
+    // x,y,z
+    let coord = || verify(int::<i64>(), |c| c.unsigned_abs() <= MAX_COORD, "coordinate out of range");
+    let point = separated_pair(coord(), tag(","), separated_pair(coord(), tag(","), coord()));
+    parse_lines(text, map(point, |(x, (y, z))| (x, y, z)))
 }
 
-pub fn solve_1(text: &str, connections: u32) -> i64 {
-    let points = parse_text(text);
+pub fn solve_1(text: &str, connections: u32) -> Result<Answer> {
+    let points = parse_text(text)?;
     let n = points.len();
 
-    // collect distances between every pair into min-heap
-    let mut heap = dist_heap(&points);
+    // stream pairs of points by increasing distance
+    let tree = KdTree::new(&points);
 
     // initially, each point is its own circuit
-    let mut circuit: Vec<u32> = (0..n as u32).collect();
+    let mut circuits = UnionFind::new(n);
 
     // connect points for K closest pairs
-    for _ in 0..connections {
-        let Reverse(p) = heap.pop().unwrap();
-        let (ci, cj) = (circuit[p.i], circuit[p.j]);
-        if ci != cj {
-            let ck = min(ci, cj);
-            for c in circuit.iter_mut() {
-                if *c == ci || *c == cj {
-                    *c = ck
-                }
-            }
-        }
+    for p in tree.pairs().take(connections as usize) {
+        circuits.union(p.i, p.j);
     }
 
-    let mut circuit_sizes: Vec<u32> = vec![0; n];
-    circuit.iter().for_each(|&ck| {
-        circuit_sizes[ck as usize] += 1;
-    });
-
+    let mut circuit_sizes = circuits.sizes();
     circuit_sizes.sort_by_key(|&s| Reverse(s));
+    debug!("{} circuits, largest: {:?}",
+           circuit_sizes.len(), &circuit_sizes[..min(circuit_sizes.len(), 3)]);
 
-    circuit_sizes.into_iter().take(3).product::<u32>() as i64
+    Ok(circuit_sizes.into_iter().take(3).map(|s| s as u64).product::<u64>().into())
 }
 
-pub fn parse_1(text: &str) -> i64 {
+pub fn parse_1(text: &str) -> Result<Answer> {
     solve_1(text, 1000)
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let points = parse_text(text);
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let points = parse_text(text)?;
     let n = points.len();
+    if n < 2 {
+        return Err(AocError::Input("need at least two junction boxes".to_string()));
+    }
 
-    // collect distances between every pair into min-heap
-    let mut heap = dist_heap(&points);
+    // stream pairs of points by increasing distance
+    let tree = KdTree::new(&points);
+    let mut pairs = tree.pairs();
 
     // initially, each point is its own circuit
-    let mut circuit: Vec<u32> = (0..n as u32).collect();
+    let mut circuits = UnionFind::new(n);
 
     // connect points until there is only one circuit
+    let mut count = 0;
     loop {
-        let Reverse(p) = heap.pop().unwrap();
-        let (ci, cj) = (circuit[p.i], circuit[p.j]);
-        if ci != cj {
-            let ck = min(ci, cj);
-            for c in circuit.iter_mut() {
-                if *c == ci || *c == cj {
-                    *c = ck
-                }
-            }
-            if circuit.iter().all(|&c| c == circuit[0]) {
-                return points[p.i].0 * points[p.j].0
+        let p = pairs.next().unwrap();
+        count += 1;
+        if circuits.union(p.i, p.j) {
+            trace!("connected {:?} and {:?}", points[p.i], points[p.j]);
+            if circuits.components() == 1 {
+                debug!("last connection: {:?} and {:?}, after {} pairs",
+                       points[p.i], points[p.j], count);
+                return Ok((points[p.i].0 * points[p.j].0).into())
             }
         }
     }
@@ -135,10 +104,22 @@ mod tests {
 ";
     #[test]
     fn test8_parse1() {
-        assert_eq!(solve_1(&INPUT_TEXT_1, 10), 40);
+        assert_eq!(solve_1(INPUT_TEXT_1, 10), Ok(Answer::from(40)));
     }
     #[test]
     fn test8_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 25272);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(25272)));
+    }
+    #[test]
+    fn test8_errors() {
+        assert_eq!(parse_1("1,2,3\n4,5\n"),
+                   Err(AocError::parse(2, 4, "", "expected \",\"")));
+        assert_eq!(parse_1("1,2,3\n4,-,6\n"),
+                   Err(AocError::parse(2, 3, "-,6", "expected number")));
+        assert!(matches!(parse_2("1,2,3\n"), Err(AocError::Input(_))));
+        assert_eq!(parse_2("0,0,0\n4000000000,0,0\n"),
+                   Err(AocError::parse(2, 1, "4000000000", "coordinate out of range")));
+        assert_eq!(parse_2("0,0,-536870912\n536870912,536870912,536870912\n"),
+                   Ok(Answer::from(0)));
     }
 }
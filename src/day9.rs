@@ -1,20 +1,23 @@
 #![allow(clippy::needless_range_loop)]
 
-use itertools::{iproduct, Itertools};
+use itertools::Itertools;
 use std::{cmp::{max, min}, collections::{HashMap, HashSet}};
-
-fn parse_text(text: &str) -> Vec<(i64, i64)> {
-    text.lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.split(",")
-             .map(|s| s.parse::<i64>().unwrap())
-             .collect_tuple() // from Itertools
-             .unwrap())
-        .collect()
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::grid::{Grid, Pos};
+use crate::log::{debug, log_enabled, trace};
+use crate::parser::{int, parse_lines, separated_pair, tag};
+use crate::solver::DaySolver;
+
+pub const SOLVER: DaySolver = DaySolver::new("day9", 9, &[parse_1, parse_2]);
+
+fn parse_text(text: &str) -> Result<Vec<(i64, i64)>> {
+    // x,y
+    parse_lines(text, separated_pair(int(), tag(","), int()))
 }
 
-pub fn parse_1(text: &str) -> i64 {
-    let points = parse_text(text);
+pub fn parse_1(text: &str) -> Result<Answer> {
+    let points = parse_text(text)?;
     let n = points.len();
     let mut area = 0;
 
@@ -26,7 +29,7 @@ pub fn parse_1(text: &str) -> i64 {
         }
     }
 
-    area
+    Ok(area.into())
 }
 
 // "Coordinate compression":
@@ -42,7 +45,7 @@ fn remap_numbers(coords: &[i64]) -> (HashMap<i64, usize>, Vec<i64>) {
     (map, rev)
 }
 
-fn mark_edge(mark_grid: &mut [Vec<bool>], a: (usize, usize), b: (usize, usize)) {
+fn mark_edge(mark_grid: &mut Grid<bool>, a: Pos, b: Pos) {
     let (xa, ya) = a;
     let (xb, yb) = b;
     if ya == yb {
@@ -50,43 +53,40 @@ fn mark_edge(mark_grid: &mut [Vec<bool>], a: (usize, usize), b: (usize, usize))
         let xmin = min(xa, xb);
         let xmax = max(xa, xb);
         for x in xmin..=xmax {
-            mark_grid[x][ya] = true;
+            mark_grid[(x, ya)] = true;
         }
     } else {
         // vertical
         let ymin = min(ya, yb);
         let ymax = max(ya, yb);
         for y in ymin..=ymax {
-            mark_grid[xa][y] = true;
+            mark_grid[(xa, y)] = true;
         }
     }
 }
 
-fn neighbours(xmax: usize, ymax: usize, (x, y): (usize, usize))
-              -> impl Iterator<Item = (usize, usize)> {
-    let xs = vec![x.overflowing_sub(1).0, x, x+1];
-    let ys = vec![y.overflowing_sub(1).0, y, y+1];
-    iproduct!(xs.into_iter(), ys.into_iter())
-        .filter(move |&(xn, yn)| xn <= xmax && yn <= ymax && (xn, yn) != (x, y))
-}
-
-fn write_grid_image(mark_grid: &[Vec<bool>],
+fn write_grid_image(mark_grid: &Grid<bool>,
                     col: image::Rgb<u8>,
-                    name: &str) {
-    let mut imgbuf = image::ImageBuffer::new(mark_grid.len() as u32,
-                                             mark_grid[0].len() as u32);
+                    name: &str) -> Result<()> {
+    let mut imgbuf = image::ImageBuffer::new(mark_grid.width() as u32,
+                                             mark_grid.height() as u32);
     for (x, y, pixel) in imgbuf.enumerate_pixels_mut() {
-        if mark_grid[x as usize][y as usize] {
+        if mark_grid[(x as usize, y as usize)] {
             *pixel = col;
         }
     }
-    imgbuf.save(name).unwrap();
+    imgbuf.save(name)
+        .map_err(|e| AocError::Io(format!("failed to write {}: {}", name, e)))
 }
 
-fn render_grid(remapped_points: &[(usize, usize)], xmax: usize, ymax: usize)
-               -> Vec<Vec<bool>> {
+fn render_mark(&b: &bool) -> char {
+    if b { '#' } else { '.' }
+}
+
+fn render_grid(remapped_points: &[Pos], xmax: usize, ymax: usize)
+               -> Result<Grid<bool>> {
     // start with all points unmarked
-    let mut mark_grid: Vec<Vec<bool>> = vec![vec![false; ymax+1]; xmax+1];
+    let mut mark_grid = Grid::new(xmax+1, ymax+1, false);
     // mark points on the edges between points
     let n = remapped_points.len();
     for i in 0..n {
@@ -95,56 +95,63 @@ fn render_grid(remapped_points: &[(usize, usize)], xmax: usize, ymax: usize)
                   remapped_points[(i+1) % n]);
     }
 
-    // println!("{:?}", mark_grid);
-    write_grid_image(&mark_grid, image::Rgb([0, 0, 255]), "9-edges.png");
+    trace!("edges:\n{}", mark_grid.render(render_mark));
+    if log_enabled!(Debug) {
+        write_grid_image(&mark_grid, image::Rgb([0, 0, 255]), "9-edges.png")?;
+    }
 
     // iteratively grow the set of def_outside points:
     // - start from outer edge of the grid: initial fringe
     // - compute next fringe: set of new neighbours that are unmarked
     // - merge prev fridge into def_outside
-    let mut def_outside: HashSet<(usize, usize)> = HashSet::new(); // empty
-    let mut fringe: HashSet<(usize, usize)> =
+    let mut def_outside: HashSet<Pos> = HashSet::new(); // empty
+    let mut fringe: HashSet<Pos> =
         // definitely outside:
         // unmarked points that are on the outer edge of the grid
-        iproduct!(0..=xmax, 0..=ymax)
+        mark_grid.positions()
         .filter(|&(x, y)| x == 0 || x == xmax || y == 0 || y == ymax)
-        .filter(|&(x, y)| !mark_grid[x][y])
+        .filter(|&p| !mark_grid[p])
         .collect();
     loop {
-        let next_fringe: HashSet<(usize, usize)> =
+        let next_fringe: HashSet<Pos> =
             // neighbours of fringe points that are unmarked and not
             // in def_outside yet
             fringe
             .iter()
-            .flat_map(|&(x, y)| neighbours(xmax, ymax, (x, y)))
-            .filter(|&(x, y)| !mark_grid[x][y] && !def_outside.contains(&(x, y)))
+            .flat_map(|&p| mark_grid.neighbours8(p))
+            .filter(|p| !mark_grid[*p] && !def_outside.contains(p))
             .collect();
         if next_fringe.is_empty() {
             break;
         } else {
-            fringe.iter().for_each(|&(x, y)| {
-               def_outside.insert((x, y));
+            fringe.iter().for_each(|&p| {
+               def_outside.insert(p);
             });
             fringe = next_fringe;
         }
     }
     // mark points not in def_outside as being inside
-    iproduct!(0..=xmax, 0..=ymax)
-        .for_each(|(x, y)| {
-            if !mark_grid[x][y] && !def_outside.contains(&(x, y)) {
-                mark_grid[x][y] = true;
-            }
-        });
+    for p in mark_grid.positions() {
+        if !mark_grid[p] && !def_outside.contains(&p) {
+            mark_grid[p] = true;
+        }
+    }
 
-    // println!("{:?}", mark_grid);
-    write_grid_image(&mark_grid, image::Rgb([0, 255, 0]), "9-filled.png");
+    trace!("filled:\n{}", mark_grid.render(render_mark));
+    if log_enabled!(Debug) {
+        write_grid_image(&mark_grid, image::Rgb([0, 255, 0]), "9-filled.png")?;
+        debug!("wrote 9-edges.png and 9-filled.png");
+    }
 
-    mark_grid
+    Ok(mark_grid)
 }
 
-pub fn parse_2(text: &str) -> i64 {
-    let points = parse_text(text);
+pub fn parse_2(text: &str) -> Result<Answer> {
+    let points = parse_text(text)?;
     let n = points.len();
+    if n == 0 {
+        return Err(AocError::Input("no red tiles".to_string()));
+    }
 
     // compute "compressed" coordinates, equivalent to original
     // coordinates, but within much smaller ranges
@@ -152,20 +159,21 @@ pub fn parse_2(text: &str) -> i64 {
     let ycoords: Vec<i64> = points.iter().map(|&p| p.1).collect();
     let (xmap, xrev) = remap_numbers(&xcoords);
     let (ymap, yrev) = remap_numbers(&ycoords);
-    // println!("{:?}", xrev);
-    // println!("{:?}", yrev);
+    debug!("compressed grid: {} x {}", xrev.len(), yrev.len());
+    trace!("x coords: {:?}", xrev);
+    trace!("y coords: {:?}", yrev);
 
-    let remapped_points: Vec<(usize, usize)> = points
+    let remapped_points: Vec<Pos> = points
         .iter()
         .map(|&(x, y)| (xmap[&x], ymap[&y]))
         .collect();
-    // println!("{:?}", remapped_points);
+    trace!("remapped points: {:?}", remapped_points);
 
     // draw grid of places inside/outside the points loop, in compressed
     // coordinates
     let xmax = xrev.len() - 1;
     let ymax = yrev.len() - 1;
-    let mark_grid = render_grid(&remapped_points, xmax, ymax);
+    let mark_grid = render_grid(&remapped_points, xmax, ymax)?;
 
     // find valid rectangles in compressed coordinates,
     // then compute max area in original space
@@ -177,8 +185,8 @@ pub fn parse_2(text: &str) -> i64 {
             let (xb, yb) = remapped_points[j];
 
             // rectangle is valid if all points inside are inside the loop
-            if (min(xa, xb)..=max(xa, xb))
-                .flat_map(|x| &mark_grid[x][min(ya, yb)..=max(ya, yb)])
+            if (min(ya, yb)..=max(ya, yb))
+                .flat_map(|y| &mark_grid.row(y)[min(xa, xb)..=max(xa, xb)])
                 .all(|&b| b) {
                     let a = ((points[i].0 - points[j].0).abs() + 1)
                         * ((points[i].1 - points[j].1).abs() + 1);
@@ -187,7 +195,7 @@ pub fn parse_2(text: &str) -> i64 {
         }
     }
 
-    area
+    Ok(area.into())
 }
 
 #[cfg(test)]
@@ -206,10 +214,16 @@ mod tests {
 ";
     #[test]
     fn test9_parse1() {
-        assert_eq!(parse_1(&INPUT_TEXT_1), 50);
+        assert_eq!(parse_1(INPUT_TEXT_1), Ok(Answer::from(50)));
     }
     #[test]
     fn test9_parse2() {
-        assert_eq!(parse_2(&INPUT_TEXT_1), 24);
+        assert_eq!(parse_2(INPUT_TEXT_1), Ok(Answer::from(24)));
+    }
+    #[test]
+    fn test9_errors() {
+        assert_eq!(parse_1("7,1\n7 3\n"),
+                   Err(AocError::parse(2, 2, "", "expected \",\"")));
+        assert!(matches!(parse_2("\n"), Err(AocError::Input(_))));
     }
 }
@@ -0,0 +1,218 @@
+// Combination lock dial: positions 0..size, turned left (down) or
+// right (up) one click at a time, wrapping around
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+// 'L' or 'R', as in the puzzle input
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self { Turn::Left => 'L', Turn::Right => 'R' })
+    }
+}
+
+// what one rotation did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub start: u64,
+    pub turn: Turn,
+    pub clicks: u64,
+    pub end: u64,
+    // ended on 0
+    pub landed: bool,
+    // clicks reaching 0
+    pub passes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dial {
+    size: u64,
+    pos: u64,
+    // rotations ending on 0
+    landings: u64,
+    // clicks reaching 0, whether the rotation stops there or not; may
+    // not fit in a u64 after several rotations
+    passes: u128,
+}
+
+impl Dial {
+    pub fn new(size: u64, start: u64) -> Dial {
+        assert!(start < size, "dial start {} out of 0..{}", start, size);
+        Dial{ size, pos: start, landings: 0, passes: 0 }
+    }
+
+    // clicks reaching 0 when turning from pos, without moving; written
+    // so that nothing overflows, even for a size near u64::MAX
+    pub fn zeros(&self, turn: Turn, clicks: u64) -> u64 {
+        match turn {
+            // 0 is size - pos clicks away, then every size clicks
+            Turn::Right => clicks / self.size
+                + u64::from(clicks % self.size >= self.size - self.pos),
+            // pos clicks away, or size clicks from 0 itself
+            Turn::Left if self.pos == 0 => clicks / self.size,
+            Turn::Left if clicks < self.pos => 0,
+            Turn::Left => (clicks - self.pos) / self.size + 1,
+        }
+    }
+
+    pub fn rotate(&mut self, turn: Turn, clicks: u64) -> Step {
+        let start = self.pos;
+        let passes = self.zeros(turn, clicks);
+        self.pos = self.moved(turn, clicks % self.size);
+        self.passes += u128::from(passes);
+        if self.pos == 0 {
+            self.landings += 1;
+        }
+        Step{ start, turn, clicks, end: self.pos, landed: self.pos == 0, passes }
+    }
+
+    // position k < size clicks away
+    fn moved(&self, turn: Turn, k: u64) -> u64 {
+        match turn {
+            Turn::Right if k >= self.size - self.pos => k - (self.size - self.pos),
+            Turn::Right => self.pos + k,
+            Turn::Left if k <= self.pos => self.pos - k,
+            Turn::Left => self.size - (k - self.pos),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn landings(&self) -> u64 {
+        self.landings
+    }
+
+    pub fn passes(&self) -> u128 {
+        self.passes
+    }
+}
+
+// rotations visiting some positions in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub rotations: Vec<(Turn, u64)>,
+    pub clicks: u128,
+    pub passes: u128,
+}
+
+// one rotation per line, e.g. L68
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (turn, clicks) in &self.rotations {
+            writeln!(f, "{}{}", turn, clicks)?;
+        }
+        Ok(())
+    }
+}
+
+impl Dial {
+    // shortest rotations from the current position to every target in
+    // turn, or with fewest zero passes, then shortest, if min_passes;
+    // a target the dial is already on needs no rotation. Each rotation
+    // ends at a given position, so choosing each one on its own is
+    // optimal for the whole plan.
+    pub fn plan(&self, targets: &[u64], min_passes: bool) -> Plan {
+        let mut dial = Dial::new(self.size, self.pos);
+        let mut plan = Plan{ rotations: vec![], clicks: 0, passes: 0 };
+        for &target in targets {
+            assert!(target < self.size, "target {} out of 0..{}", target, self.size);
+            if target == dial.pos {
+                continue
+            }
+            let right = if target > dial.pos {
+                target - dial.pos
+            } else {
+                self.size - (dial.pos - target)
+            };
+            let options = [(Turn::Right, right), (Turn::Left, self.size - right)];
+            let cost = |&(turn, clicks): &(Turn, u64)| {
+                let passes = if min_passes { dial.zeros(turn, clicks) } else { 0 };
+                (passes, clicks)
+            };
+            let (turn, clicks) = *options.iter().min_by_key(|o| cost(o)).unwrap();
+            let step = dial.rotate(turn, clicks);
+            plan.rotations.push((turn, clicks));
+            plan.clicks += u128::from(clicks);
+            plan.passes += u128::from(step.passes);
+        }
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        let mut d = Dial::new(100, 50);
+        d.rotate(Turn::Left, 68);
+        assert_eq!((d.position(), d.landings(), d.passes()), (82, 0, 1));
+        d.rotate(Turn::Left, 30);
+        assert_eq!((d.position(), d.landings(), d.passes()), (52, 0, 1));
+        d.rotate(Turn::Right, 48);
+        assert_eq!((d.position(), d.landings(), d.passes()), (0, 1, 2));
+        // from 0, a full turn comes back once
+        d.rotate(Turn::Left, 100);
+        assert_eq!((d.position(), d.landings(), d.passes()), (0, 2, 3));
+        d.rotate(Turn::Right, 250);
+        assert_eq!((d.position(), d.landings(), d.passes()), (50, 2, 5));
+        d.rotate(Turn::Left, 0);
+        assert_eq!((d.position(), d.landings(), d.passes()), (50, 2, 5));
+    }
+    #[test]
+    fn test_small_dial() {
+        let mut d = Dial::new(1, 0);
+        d.rotate(Turn::Right, 3);
+        assert_eq!((d.position(), d.landings(), d.passes()), (0, 1, 3));
+        let mut d = Dial::new(60, 10);
+        assert_eq!(d.zeros(Turn::Left, 130), 3);
+        d.rotate(Turn::Left, 130);
+        assert_eq!(d.position(), 0);
+    }
+    #[test]
+    fn test_large_values() {
+        let mut d = Dial::new(100, 50);
+        d.rotate(Turn::Right, u64::MAX);
+        assert_eq!((d.position(), d.passes()), (65, (u64::MAX as u128 + 50) / 100));
+        d.rotate(Turn::Left, u64::MAX);
+        assert_eq!(d.position(), 50);
+        let mut d = Dial::new(1, 0);
+        d.rotate(Turn::Right, u64::MAX);
+        d.rotate(Turn::Right, u64::MAX);
+        assert_eq!(d.passes(), 2 * u64::MAX as u128);
+        // positions near the size
+        let mut d = Dial::new(u64::MAX, u64::MAX - 2);
+        assert_eq!(d.rotate(Turn::Right, 5).end, 3);
+        assert_eq!(d.rotate(Turn::Left, 5).end, u64::MAX - 2);
+        assert_eq!(d.passes(), 2);
+        assert_eq!(d.plan(&[1, u64::MAX - 1], false).to_string(), "R3\nL2\n");
+    }
+    #[test]
+    fn test_plan() {
+        let d = Dial::new(100, 50);
+        let plan = d.plan(&[82, 52, 0, 0, 99], false);
+        assert_eq!(plan.to_string(), "R32\nL30\nR48\nL1\n");
+        assert_eq!((plan.clicks, plan.passes), (111, 1));
+        // from 90 to 20 the long way round avoids 0
+        let d = Dial::new(100, 90);
+        let plan = d.plan(&[20], false);
+        assert_eq!((plan.to_string(), plan.passes), ("R30\n".to_string(), 1));
+        let plan = d.plan(&[20], true);
+        assert_eq!((plan.to_string(), plan.passes), ("L70\n".to_string(), 0));
+        // landing on 0 cannot be avoided
+        assert_eq!(d.plan(&[0, 10], true).passes, 1);
+        assert_eq!(d.plan(&[], true).rotations, []);
+    }
+}
@@ -0,0 +1,90 @@
+// Crate-wide error type: solvers report malformed input through it
+// rather than panicking, so main can print a readable diagnostic
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AocError {
+    // malformed input at a given position: line and column are 1-based,
+    // text is the offending piece of input
+    Parse { line: usize, column: usize, text: String, msg: String },
+    // input is malformed as a whole (e.g. empty, inconsistent)
+    Input(String),
+    // bad command line
+    Usage(String),
+    // failure to read or write a file
+    Io(String),
+}
+
+pub type Result<T> = std::result::Result<T, AocError>;
+
+impl AocError {
+    pub fn parse(line: usize, column: usize, text: &str, msg: &str) -> AocError {
+        AocError::Parse{ line, column, text: text.to_string(), msg: msg.to_string() }
+    }
+
+    // error about sub, which must be a slice of line
+    pub fn at(line_num: usize, line: &str, sub: &str, msg: &str) -> AocError {
+        AocError::parse(line_num, column(line, sub), sub, msg)
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Parse{ line, column, text, msg } =>
+                write!(f, "line {}, column {}: {}: {:?}", line, column, msg, text),
+            AocError::Input(msg) => write!(f, "invalid input: {}", msg),
+            AocError::Usage(msg) => write!(f, "{}", msg),
+            AocError::Io(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AocError {}
+
+// 1-based column of sub within line; sub must be a slice of line
+pub fn column(line: &str, sub: &str) -> usize {
+    let offset = (sub.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    if offset <= line.len() { offset + 1 } else { 1 }
+}
+
+// parse field (a slice of line), reporting its position on failure
+pub fn parse_field<T: FromStr>(line_num: usize, line: &str, field: &str) -> Result<T> {
+    field.parse()
+        .map_err(|_| AocError::at(line_num, line, field, "invalid number"))
+}
+
+// non-empty lines of text, with their 1-based line number
+pub fn numbered_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column() {
+        let line = "12,x4,56";
+        assert_eq!(column(line, &line[3..5]), 4);
+        assert_eq!(column(line, "other"), 1);
+    }
+    #[test]
+    fn test_parse_field() {
+        let line = "12,x4";
+        assert_eq!(parse_field::<i64>(3, line, &line[0..2]), Ok(12));
+        let e = parse_field::<i64>(3, line, &line[3..5]).unwrap_err();
+        assert_eq!(e, AocError::parse(3, 4, "x4", "invalid number"));
+        assert_eq!(e.to_string(), "line 3, column 4: invalid number: \"x4\"");
+    }
+    #[test]
+    fn test_numbered_lines() {
+        let v: Vec<_> = numbered_lines("\na\n\nb\n").collect();
+        assert_eq!(v, [(2, "a"), (4, "b")]);
+    }
+}
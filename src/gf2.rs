@@ -0,0 +1,144 @@
+// Linear algebra over GF(2), with bit vectors packed in u64
+//
+// Solves sum(x_j * columns[j]) = target, where + is XOR: Gaussian
+// elimination yields one particular solution and a basis of the null
+// space, and every solution is the particular one XOR some combination
+// of the basis.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solutions {
+    // bit j set if x_j = 1, with free variables at 0
+    pub particular: u64,
+    // one vector per free variable
+    pub null_space: Vec<u64>,
+}
+
+// all x such that the XOR of columns[j] for which x_j = 1 is target,
+// or None if there are none; at most 64 columns
+pub fn solve(columns: &[u64], target: u64) -> Option<Solutions> {
+    assert!(columns.len() <= 64);
+
+    // one equation per bit: (variables mask, right-hand side)
+    let mut rows: Vec<(u64, bool)> = (0..64)
+        .map(|bit| (columns.iter()
+                    .enumerate()
+                    .filter(|&(_, c)| (c >> bit) & 1 == 1)
+                    .fold(0, |acc, (j, _)| acc | 1 << j),
+                    (target >> bit) & 1 == 1))
+        .filter(|&(vars, rhs)| vars != 0 || rhs)
+        .collect();
+
+    // reduced row echelon form
+    let mut pivots: Vec<usize> = vec![];
+    for j in 0..columns.len() {
+        let r = pivots.len();
+        let Some(p) = (r..rows.len()).find(|&i| (rows[i].0 >> j) & 1 == 1) else {
+            continue
+        };
+        rows.swap(r, p);
+        let pivot = rows[r];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r && (row.0 >> j) & 1 == 1 {
+                row.0 ^= pivot.0;
+                row.1 ^= pivot.1;
+            }
+        }
+        pivots.push(j);
+    }
+    // remaining rows read 0 = rhs
+    if rows[pivots.len()..].iter().any(|&(_, rhs)| rhs) {
+        return None
+    }
+
+    let particular = pivots.iter()
+        .zip(&rows)
+        .filter(|&(_, &(_, rhs))| rhs)
+        .fold(0, |acc, (&j, _)| acc | 1 << j);
+    // setting free variable f to 1 flips the pivots of rows using it
+    let null_space = (0..columns.len())
+        .filter(|j| !pivots.contains(j))
+        .map(|f| pivots.iter()
+             .zip(&rows)
+             .filter(|&(_, &(vars, _))| (vars >> f) & 1 == 1)
+             .fold(1 << f, |acc, (&j, _)| acc | 1 << j))
+        .collect();
+
+    Some(Solutions{ particular, null_space })
+}
+
+impl Solutions {
+    // solution with the fewest variables set. Each basis vector sets a
+    // free variable no other one does, so combining c of them sets at
+    // least c variables: only combinations smaller than the best
+    // solution so far need trying, and the particular solution sets at
+    // most one variable per pivot.
+    pub fn min_weight(&self) -> u64 {
+        let mut best = self.particular;
+        self.search(0, self.particular, 0, &mut best);
+        best
+    }
+
+    // try adding basis vectors from k on to x, made of c vectors
+    fn search(&self, k: usize, x: u64, c: u32, best: &mut u64) {
+        if x.count_ones() < best.count_ones() {
+            *best = x;
+        }
+        if c + 1 >= best.count_ones() {
+            return
+        }
+        for (j, v) in self.null_space.iter().enumerate().skip(k) {
+            self.search(j + 1, x ^ v, c + 1, best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // XOR of the selected columns
+    fn apply(columns: &[u64], x: u64) -> u64 {
+        columns.iter()
+            .enumerate()
+            .filter(|&(j, _)| (x >> j) & 1 == 1)
+            .fold(0, |acc, (_, c)| acc ^ c)
+    }
+
+    #[test]
+    fn test_solve() {
+        let columns = [0b1000, 0b1010, 0b0100, 0b1100, 0b0101, 0b0011];
+        let s = solve(&columns, 0b0110).unwrap();
+        assert_eq!(apply(&columns, s.particular), 0b0110);
+        assert_eq!(s.null_space.len(), 2);
+        for &v in &s.null_space {
+            assert_eq!(apply(&columns, v), 0);
+        }
+        assert_eq!(s.min_weight().count_ones(), 2);
+    }
+    #[test]
+    fn test_unsolvable() {
+        assert_eq!(solve(&[0b01, 0b01], 0b10), None);
+        assert_eq!(solve(&[], 0b1), None);
+    }
+    #[test]
+    fn test_large_null_space() {
+        // 40 copies of the same column: any one of them will do
+        let columns = [0b1; 40];
+        let s = solve(&columns, 0b1).unwrap();
+        assert_eq!(s.null_space.len(), 39);
+        assert_eq!(s.min_weight().count_ones(), 1);
+        // 60 columns: singles, then pairs of the 6 bits, for a target
+        // needing 2 of them
+        let singles = (0..6).map(|b| 1u64 << b);
+        let pairs = (0..6).flat_map(|a| (a + 1..6).map(move |b| 1u64 << a | 1 << b));
+        let columns: Vec<u64> = singles.chain(pairs).cycle().take(60).collect();
+        let x = solve(&columns, 0b101101).unwrap().min_weight();
+        assert_eq!(apply(&columns, x), 0b101101);
+        assert_eq!(x.count_ones(), 2);
+    }
+    #[test]
+    fn test_trivial() {
+        assert_eq!(solve(&[], 0), Some(Solutions{ particular: 0, null_space: vec![] }));
+        assert_eq!(solve(&[0b1, 0b1], 0).unwrap().min_weight(), 0);
+    }
+}
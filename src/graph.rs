@@ -0,0 +1,276 @@
+// Directed graphs with string labels: cycle detection, topological
+// order and path counting
+
+use std::collections::HashMap;
+
+use crate::error::{AocError, Result};
+
+// most nodes count_paths_via can be asked to visit
+const MAX_VIA: usize = 16;
+// most path counts it may keep, one per reachable node and set of via
+// nodes visited
+const MAX_COUNTS: usize = 1 << 22;
+
+#[derive(Debug, Default)]
+pub struct Graph<'a> {
+    labels: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    New,
+    // on the current depth-first path
+    Open,
+    Done,
+}
+
+impl<'a> Graph<'a> {
+    pub fn new() -> Graph<'a> {
+        Default::default()
+    }
+
+    // id of label, added if new
+    pub fn node(&mut self, label: &'a str) -> usize {
+        if let Some(&id) = self.ids.get(label) {
+            return id
+        }
+        self.labels.push(label);
+        self.edges.push(vec![]);
+        self.ids.insert(label, self.labels.len() - 1);
+        self.labels.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: &'a str, to: &'a str) {
+        let (from, to) = (self.node(from), self.node(to));
+        self.edges[from].push(to);
+    }
+
+    pub fn id(&self, label: &str) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: usize) -> &'a str {
+        self.labels[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    // depth-first search from roots, not going through skipped nodes:
+    // the nodes reached in topological order, or the first loop found,
+    // as a path ending where it starts
+    fn visit<I, F>(&self, roots: I, skip: F) -> std::result::Result<Vec<usize>, Vec<usize>>
+    where I: IntoIterator<Item = usize>, F: Fn(usize) -> bool {
+        let mut mark = vec![Mark::New; self.len()];
+        let mut order = vec![];
+        for root in roots {
+            if mark[root] != Mark::New || skip(root) {
+                continue
+            }
+            // path of (node, next successor to look at)
+            let mut stack = vec![(root, 0)];
+            mark[root] = Mark::Open;
+            while let Some(&(node, k)) = stack.last() {
+                let Some(&next) = self.edges[node].get(k) else {
+                    mark[node] = Mark::Done;
+                    order.push(node);
+                    stack.pop();
+                    continue
+                };
+                stack.last_mut().unwrap().1 += 1;
+                match mark[next] {
+                    _ if skip(next) => (),
+                    Mark::New => {
+                        mark[next] = Mark::Open;
+                        stack.push((next, 0));
+                    }
+                    Mark::Open => {
+                        let first = stack.iter().position(|&(n, _)| n == next).unwrap();
+                        let mut cycle: Vec<usize> = stack[first..].iter().map(|&(n, _)| n).collect();
+                        cycle.push(next);
+                        return Err(cycle)
+                    }
+                    Mark::Done => (),
+                }
+            }
+        }
+        order.reverse();
+        Ok(order)
+    }
+
+    fn cycle_error(&self, cycle: Vec<usize>) -> AocError {
+        let labels: Vec<&str> = cycle.into_iter().map(|id| self.label(id)).collect();
+        AocError::Input(format!("cycle: {}", labels.join(" -> ")))
+    }
+
+    // labels along some loop, e.g. [a, b, a], if there is one
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn find_cycle(&self) -> Option<Vec<&'a str>> {
+        self.visit(0..self.len(), |_| false).err()
+            .map(|cycle| cycle.into_iter().map(|id| self.label(id)).collect())
+    }
+
+    // every node, each before its successors
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn topo_order(&self) -> Result<Vec<usize>> {
+        self.visit(0..self.len(), |_| false).map_err(|cycle| self.cycle_error(cycle))
+    }
+
+    // nodes reachable from start without going through skipped ones,
+    // each before its successors
+    pub fn topo_order_from<F>(&self, start: usize, skip: F) -> Result<Vec<usize>>
+    where F: Fn(usize) -> bool {
+        self.visit([start], skip).map_err(|cycle| self.cycle_error(cycle))
+    }
+
+    // number of paths from one label to another; an error if either is
+    // unknown, or if a loop is reachable from the start, since there
+    // may then be infinitely many
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn count_paths(&self, from: &str, to: &str) -> Result<u128> {
+        self.count_paths_via(from, to, &[], &[])
+    }
+
+    // number of paths from one label to another that visit every label
+    // of via, in any order, and none of avoid
+    pub fn count_paths_via(&self, from: &str, to: &str, via: &[&str], avoid: &[&str])
+                           -> Result<u128> {
+        let id = |label: &str| self.id(label)
+            .ok_or_else(|| AocError::Input(format!("unknown node {}", label)));
+        let from = id(from)?;
+        let to = id(to)?;
+        if via.len() > MAX_VIA {
+            return Err(AocError::Input(
+                format!("too many nodes to visit: {}, at most {}", via.len(), MAX_VIA)));
+        }
+
+        // bit of each node of via in the visited mask
+        let mut bit = vec![0usize; self.len()];
+        for (k, &label) in via.iter().enumerate() {
+            bit[id(label)?] |= 1 << k;
+        }
+        let mut avoided = vec![false; self.len()];
+        for &label in avoid {
+            if let Some(a) = self.id(label) {
+                avoided[a] = true;
+            }
+        }
+        if avoided[from] {
+            return Ok(0)
+        }
+
+        let order = self.topo_order_from(from, |n| avoided[n])?;
+        let masks = 1 << via.len();
+        if order.len() * masks > MAX_COUNTS {
+            return Err(AocError::Input(
+                format!("too many paths to track: {} nodes, {} to visit", order.len(), via.len())));
+        }
+        // index of each reachable node in order
+        let mut slot = vec![usize::MAX; self.len()];
+        for (k, &node) in order.iter().enumerate() {
+            slot[node] = k;
+        }
+        if slot[to] == usize::MAX {
+            return Ok(0)
+        }
+
+        // paths from the start to each node, by set of via nodes visited
+        let mut counts = vec![vec![0u128; masks]; order.len()];
+        counts[0][bit[from]] = 1;
+        for (k, &node) in order.iter().enumerate() {
+            if node == to {
+                continue
+            }
+            for &next in &self.edges[node] {
+                if avoided[next] {
+                    continue
+                }
+                for mask in 0..masks {
+                    let c = counts[k][mask];
+                    counts[slot[next]][mask | bit[next]] += c;
+                }
+            }
+        }
+        Ok(counts[slot[to]][masks - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph<'a>(edges: &[(&'a str, &'a str)]) -> Graph<'a> {
+        let mut g = Graph::new();
+        for &(a, b) in edges {
+            g.add_edge(a, b);
+        }
+        g
+    }
+
+    #[test]
+    fn test_topo_order() {
+        let g = graph(&[("c", "d"), ("a", "b"), ("b", "c"), ("a", "c")]);
+        let order: Vec<&str> = g.topo_order_from(g.id("a").unwrap(), |_| false).unwrap()
+            .into_iter()
+            .map(|id| g.label(id))
+            .collect();
+        assert_eq!(order, ["a", "b", "c", "d"]);
+        let order: Vec<&str> = g.topo_order().unwrap().into_iter().map(|id| g.label(id)).collect();
+        assert_eq!(order, ["a", "b", "c", "d"]);
+        assert_eq!(g.find_cycle(), None);
+        assert_eq!(g.topo_order_from(g.id("b").unwrap(), |_| false).unwrap().len(), 3);
+        let c = g.id("c").unwrap();
+        assert_eq!(g.topo_order_from(g.id("a").unwrap(), |n| n == c).unwrap().len(), 2);
+    }
+    #[test]
+    fn test_cycle() {
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "d"), ("d", "b"), ("x", "a"),
+                        ("y", "z")]);
+        assert_eq!(g.find_cycle(), Some(vec!["b", "c", "d", "b"]));
+        assert_eq!(g.topo_order(), Err(AocError::Input("cycle: b -> c -> d -> b".to_string())));
+        assert_eq!(g.topo_order_from(g.id("x").unwrap(), |_| false),
+                   Err(AocError::Input("cycle: b -> c -> d -> b".to_string())));
+        assert!(g.count_paths("x", "d").is_err());
+        // the loop is not reachable from y
+        assert_eq!(g.count_paths("y", "z"), Ok(1));
+        // nor when avoiding one of its nodes
+        assert_eq!(g.count_paths_via("x", "c", &[], &["d"]), Ok(1));
+    }
+    #[test]
+    fn test_count_paths() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "e"), ("b", "e")]);
+        assert_eq!(g.count_paths("a", "e"), Ok(3));
+        assert_eq!(g.count_paths("a", "a"), Ok(1));
+        assert_eq!(g.count_paths("e", "a"), Ok(0));
+        assert!(g.count_paths("a", "z").is_err());
+    }
+    #[test]
+    fn test_count_paths_via() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "e"), ("b", "e")]);
+        assert_eq!(g.count_paths_via("a", "e", &["d"], &[]), Ok(2));
+        assert_eq!(g.count_paths_via("a", "e", &["d", "b"], &[]), Ok(1));
+        assert_eq!(g.count_paths_via("a", "e", &["b", "d"], &[]), Ok(1));
+        assert_eq!(g.count_paths_via("a", "e", &["b", "c"], &[]), Ok(0));
+        assert_eq!(g.count_paths_via("a", "e", &[], &["d"]), Ok(1));
+        assert_eq!(g.count_paths_via("a", "e", &["d"], &["c"]), Ok(1));
+        assert_eq!(g.count_paths_via("a", "e", &[], &["a"]), Ok(0));
+        assert!(g.count_paths_via("a", "e", &["z"], &[]).is_err());
+    }
+    #[test]
+    fn test_count_paths_limits() {
+        // a chain n0 -> n1 -> .. -> n99
+        let names: Vec<String> = (0..100).map(|k| format!("n{}", k)).collect();
+        let edges: Vec<(&str, &str)> = names.windows(2).map(|w| (&w[0][..], &w[1][..])).collect();
+        let g = graph(&edges);
+        let via: Vec<&str> = names[1..17].iter().map(|s| &s[..]).collect();
+        // too many paths to track from n0, but not from n50
+        assert!(g.count_paths_via("n0", "n99", &via, &[]).is_err());
+        let via: Vec<&str> = names[51..67].iter().map(|s| &s[..]).collect();
+        assert_eq!(g.count_paths_via("n50", "n99", &via, &[]), Ok(1));
+        let via: Vec<&str> = names[1..18].iter().map(|s| &s[..]).collect();
+        assert!(g.count_paths_via("n0", "n20", &via, &[]).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+// 2D grid with flat row-major storage, addressed by (x, y) positions
+// where x is the column and y the row
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::error::{numbered_lines, AocError, Result};
+
+pub type Pos = (usize, usize);
+
+#[cfg_attr(not(test), allow(dead_code))]
+const DIRS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const DIRS8: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0),
+                                    (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where T: Clone {
+        Grid{ width, height, cells: vec![fill; width * height] }
+    }
+
+    // parse a character map, one row per non-empty line; cell maps each
+    // character to a cell value, or None if the character is invalid
+    pub fn parse<F>(text: &str, mut cell: F) -> Result<Grid<T>>
+    where F: FnMut(char) -> Option<T> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for (n, line) in numbered_lines(text) {
+            let line = line.trim_end();
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err(AocError::at(n, line, line, "lines must all have the same length"));
+            }
+            for (k, c) in line.char_indices() {
+                cells.push(cell(c).ok_or_else(
+                    || AocError::at(n, line, &line[k..k+c.len_utf8()], "unexpected character"))?);
+            }
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Grid{ width, height, cells }),
+            _ => Err(AocError::Input("empty grid".to_string())),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.1 * self.width + pos.0])
+        } else {
+            None
+        }
+    }
+
+    // all positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    fn offsets(&self, (x, y): Pos, dirs: &'static [(isize, isize)])
+               -> impl Iterator<Item = Pos> + use<T> {
+        let (width, height) = (self.width, self.height);
+        dirs.iter().filter_map(move |&(dx, dy)| {
+            let nx = x.checked_add_signed(dx)?;
+            let ny = y.checked_add_signed(dy)?;
+            (nx < width && ny < height).then_some((nx, ny))
+        })
+    }
+
+    // orthogonal neighbours within the grid
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + use<T> {
+        self.offsets(pos, &DIRS4)
+    }
+
+    // orthogonal and diagonal neighbours within the grid
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + use<T> {
+        self.offsets(pos, &DIRS8)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    // one line of characters per row
+    pub fn render<F>(&self, f: F) -> String
+    where F: Fn(&T) -> char {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            s.extend(row.iter().map(&f));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("position {:?} outside {}x{} grid",
+                                      pos, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("position {:?} outside {}x{} grid",
+                                      pos, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse("\nab.\n.cd\n", Some).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = sample();
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[(1, 0)], 'b');
+        assert_eq!(g[(2, 1)], 'd');
+        assert_eq!(g.get((3, 0)), None);
+        assert_eq!(g.to_string(), "ab.\n.cd\n");
+    }
+    #[test]
+    fn test_parse_errors() {
+        let digit = |c: char| c.to_digit(10);
+        assert_eq!(Grid::parse("12\n3x\n", digit),
+                   Err(AocError::parse(2, 2, "x", "unexpected character")));
+        assert_eq!(Grid::parse("12\n345\n", digit),
+                   Err(AocError::parse(2, 1, "345", "lines must all have the same length")));
+        assert!(matches!(Grid::parse("\n\n", digit), Err(AocError::Input(_))));
+    }
+    #[test]
+    fn test_neighbours() {
+        let g = Grid::new(3, 3, 0);
+        assert_eq!(g.neighbours4((0, 0)).collect::<Vec<_>>(), [(1, 0), (0, 1)]);
+        assert_eq!(g.neighbours4((1, 1)).count(), 4);
+        assert_eq!(g.neighbours8((0, 0)).collect::<Vec<_>>(), [(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(g.neighbours8((1, 1)).count(), 8);
+        assert_eq!(g.neighbours8((2, 2)).count(), 3);
+    }
+    #[test]
+    fn test_rows_columns() {
+        let g = sample();
+        assert_eq!(g.row(1), ['.', 'c', 'd']);
+        assert_eq!(g.rows().count(), 2);
+        assert_eq!(g.column(2).collect::<String>(), ".d");
+        assert_eq!(g.columns().map(|c| c.collect::<String>()).collect::<Vec<_>>(),
+                   ["a.", "bc", ".d"]);
+        assert_eq!(g.positions().collect::<Vec<_>>(),
+                   [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    }
+    #[test]
+    fn test_index_mut_render() {
+        let mut g = Grid::new(2, 2, false);
+        g[(1, 0)] = true;
+        assert_eq!(g.render(|&b| if b { '#' } else { '.' }), ".#\n..\n");
+    }
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let g = Grid::new(2, 2, false);
+        let _ = g[(2, 0)];
+    }
+}
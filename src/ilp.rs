@@ -0,0 +1,162 @@
+// Exact solver for small integer linear programs of the form
+//
+//   minimise sum(x)  subject to  a·x = b,  x >= 0 integer
+//
+// where a and b have non-negative coefficients. Gaussian elimination
+// (fraction-free, over the integers) expresses pivot variables in
+// terms of the remaining free variables; those are bounded by b, since
+// every variable only adds to the rows it appears in, and are then
+// enumerated with branch and bound.
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+// divide a row by the gcd of its coefficients
+fn normalise(row: &mut [i128]) {
+    let g = row.iter().fold(0, |g, &v| gcd(g, v));
+    if g > 1 {
+        row.iter_mut().for_each(|v| *v /= g);
+    }
+}
+
+// reduced row echelon form of the augmented matrix [a | b], with
+// integer coefficients; returns the pivot column of each non-zero row,
+// or None if the system has no solution at all
+fn eliminate(m: &mut Vec<Vec<i128>>, cols: usize) -> Option<Vec<usize>> {
+    let mut pivots = vec![];
+    for c in 0..cols {
+        let r = pivots.len();
+        let Some(p) = (r..m.len()).find(|&i| m[i][c] != 0) else {
+            continue
+        };
+        m.swap(r, p);
+        if m[r][c] < 0 {
+            m[r].iter_mut().for_each(|v| *v = -*v);
+        }
+        let pivot = m[r].clone();
+        for (i, row) in m.iter_mut().enumerate() {
+            if i != r && row[c] != 0 {
+                let g = row[c];
+                for (v, &pv) in row.iter_mut().zip(&pivot) {
+                    *v = *v * pivot[c] - pv * g;
+                }
+                normalise(row);
+            }
+        }
+        pivots.push(c);
+    }
+    // remaining rows read 0 = b
+    if m[pivots.len()..].iter().any(|row| row[cols] != 0) {
+        return None
+    }
+    m.truncate(pivots.len());
+    Some(pivots)
+}
+
+struct Search<'a> {
+    // reduced rows: row[pivot] * x_pivot + sum(row[f] * x_f) = row[cols]
+    rows: &'a [Vec<i128>],
+    pivots: &'a [usize],
+    free: &'a [usize],
+    bounds: &'a [i128],
+    cols: usize,
+    x: Vec<i128>,
+    best: Option<(i128, Vec<i128>)>,
+}
+
+impl Search<'_> {
+    // assign free[k..], with free[..k] summing to partial
+    fn run(&mut self, k: usize, partial: i128) {
+        if self.best.as_ref().is_some_and(|(s, _)| partial >= *s) {
+            return
+        }
+        if k == self.free.len() {
+            self.solve_pivots(partial);
+            return
+        }
+        let f = self.free[k];
+        for v in 0..=self.bounds[f] {
+            self.x[f] = v;
+            self.run(k + 1, partial + v);
+        }
+        self.x[f] = 0;
+    }
+
+    fn solve_pivots(&mut self, partial: i128) {
+        let mut total = partial;
+        for (row, &p) in self.rows.iter().zip(self.pivots) {
+            let rest: i128 = self.free.iter().map(|&f| row[f] * self.x[f]).sum();
+            let num = row[self.cols] - rest;
+            if num % row[p] != 0 || num / row[p] < 0 || num / row[p] > self.bounds[p] {
+                return
+            }
+            self.x[p] = num / row[p];
+            total += self.x[p];
+        }
+        if self.best.as_ref().is_none_or(|(s, _)| total < *s) {
+            self.best = Some((total, self.x.clone()));
+        }
+    }
+}
+
+// non-negative integer x minimising sum(x) with a·x = b, if any
+pub fn min_sum(a: &[Vec<u64>], b: &[u64]) -> Option<Vec<u64>> {
+    assert_eq!(a.len(), b.len());
+    let cols = a.first().map_or(0, |row| row.len());
+
+    // each variable is at most the smallest target of the rows it
+    // adds to; a variable in no row is useless and stays 0
+    let bounds: Vec<i128> = (0..cols)
+        .map(|j| (0..a.len())
+             .filter(|&i| a[i][j] > 0)
+             .map(|i| (b[i] / a[i][j]) as i128)
+             .min()
+             .unwrap_or(0))
+        .collect();
+
+    let mut m: Vec<Vec<i128>> = a.iter().zip(b)
+        .map(|(row, &bi)| row.iter().map(|&v| v as i128)
+             .chain([bi as i128])
+             .collect())
+        .collect();
+    let pivots = eliminate(&mut m, cols)?;
+    let free: Vec<usize> = (0..cols).filter(|c| !pivots.contains(c)).collect();
+
+    let mut search = Search{ rows: &m, pivots: &pivots, free: &free, bounds: &bounds,
+                             cols, x: vec![0; cols], best: None };
+    search.run(0, 0);
+    search.best.map(|(_, x)| x.into_iter().map(|v| v as u64).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique() {
+        // x0 + x1 = 3, x1 = 1
+        assert_eq!(min_sum(&[vec![1, 1], vec![0, 1]], &[3, 1]), Some(vec![2, 1]));
+    }
+    #[test]
+    fn test_free_variables() {
+        // x0 + x2 = 4, x1 + x2 = 4: cheaper to use x2 only
+        assert_eq!(min_sum(&[vec![1, 0, 1], vec![0, 1, 1]], &[4, 4]), Some(vec![0, 0, 4]));
+        // useless variable stays 0
+        assert_eq!(min_sum(&[vec![2, 0]], &[6]), Some(vec![3, 0]));
+    }
+    #[test]
+    fn test_infeasible() {
+        // parity
+        assert_eq!(min_sum(&[vec![2]], &[3]), None);
+        // inconsistent rows
+        assert_eq!(min_sum(&[vec![1, 1], vec![1, 1]], &[1, 2]), None);
+        // only negative solutions: x0 + x1 = 1, x1 = 2
+        assert_eq!(min_sum(&[vec![1, 1], vec![0, 1]], &[1, 2]), None);
+    }
+    #[test]
+    fn test_empty() {
+        assert_eq!(min_sum(&[], &[]), Some(vec![]));
+        assert_eq!(min_sum(&[vec![1]], &[0]), Some(vec![0]));
+    }
+}
@@ -0,0 +1,146 @@
+// Leveled logging of intermediate solver state to stderr
+//
+// The default level is set from the command line (-v, -vv, -vvv), and
+// the AOC_LOG environment variable can override it, either globally
+// or per module: e.g. AOC_LOG=day10=trace, or AOC_LOG=info,day9=debug
+
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Off = 0,
+    Info = 1,
+    Debug = 2,
+    Trace = 3,
+}
+
+impl Level {
+    fn from_u8(n: u8) -> Level {
+        match n {
+            0 => Level::Off,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    fn parse(s: &str) -> Option<Level> {
+        match s {
+            "off" => Some(Level::Off),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => s.parse().ok().map(Level::from_u8),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+// per-module levels, from AOC_LOG
+type ModuleLevels = Vec<(String, Level)>;
+
+static DEFAULT_LEVEL: AtomicU8 = AtomicU8::new(Level::Off as u8);
+static MODULE_LEVELS: OnceLock<ModuleLevels> = OnceLock::new();
+
+pub const ENV_VAR: &str = "AOC_LOG";
+
+// parse a spec like "info,day9=debug": returns the default level, if
+// any, and per-module levels
+fn parse_spec(spec: &str) -> Result<(Option<Level>, ModuleLevels), String> {
+    let mut default = None;
+    let mut modules = vec![];
+    for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let bad = || format!("invalid {} item {:?}", ENV_VAR, item);
+        match item.split_once('=') {
+            Some((module, level)) =>
+                modules.push((module.to_string(), Level::parse(level).ok_or_else(bad)?)),
+            None => default = Some(Level::parse(item).ok_or_else(bad)?),
+        }
+    }
+    Ok((default, modules))
+}
+
+// set levels from verbosity (count of -v) and the environment
+pub fn init(verbosity: u8) -> Result<(), String> {
+    let spec = std::env::var(ENV_VAR).unwrap_or_default();
+    let (default, modules) = parse_spec(&spec)?;
+    let level = default.unwrap_or(Level::from_u8(verbosity));
+    DEFAULT_LEVEL.store(level as u8, Ordering::Relaxed);
+    MODULE_LEVELS.set(modules).ok();
+    Ok(())
+}
+
+// last component of a module path, e.g. "day10" for "aoc25::day10"
+fn module_name(module_path: &str) -> &str {
+    module_path.rsplit("::").next().unwrap()
+}
+
+pub fn enabled(module_path: &str, level: Level) -> bool {
+    let name = module_name(module_path);
+    let max = MODULE_LEVELS.get()
+        .and_then(|v| v.iter().rev().find(|(m, _)| m == name))
+        .map(|&(_, l)| l)
+        .unwrap_or_else(|| Level::from_u8(DEFAULT_LEVEL.load(Ordering::Relaxed)));
+    level <= max
+}
+
+pub fn write(module_path: &str, level: Level, args: fmt::Arguments) {
+    eprintln!("[{} {}] {}", module_name(module_path), level.name(), args);
+}
+
+macro_rules! log_at {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::log::enabled(module_path!(), $level) {
+            $crate::log::write(module_path!(), $level, format_args!($($arg)*));
+        }
+    };
+}
+
+// whether logging at a level is on for the calling module
+macro_rules! log_enabled {
+    ($level:ident) => { $crate::log::enabled(module_path!(), $crate::log::Level::$level) };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::log::log_at!($crate::log::Level::Info, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { $crate::log::log_at!($crate::log::Level::Debug, $($arg)*) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => { $crate::log::log_at!($crate::log::Level::Trace, $($arg)*) };
+}
+
+pub(crate) use {log_at, log_enabled, info, debug, trace};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spec() {
+        assert_eq!(parse_spec(""), Ok((None, vec![])));
+        assert_eq!(parse_spec("debug"), Ok((Some(Level::Debug), vec![])));
+        assert_eq!(parse_spec("1,day10=trace"),
+                   Ok((Some(Level::Info), vec![("day10".to_string(), Level::Trace)])));
+        assert!(parse_spec("day10=loud").is_err());
+        assert!(parse_spec("loud").is_err());
+    }
+    #[test]
+    fn test_module_name() {
+        assert_eq!(module_name("aoc25::day10"), "day10");
+        assert_eq!(module_name("day10"), "day10");
+    }
+}
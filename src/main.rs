@@ -1,5 +1,37 @@
 use std::env;
 use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use answers::{Answers, Check};
+use cli::{Command, Format, Options};
+use error::{AocError, Result};
+use report::Report;
+use solver::{Params, Solver};
+
+mod answer;
+mod answers;
+mod bench;
+mod cli;
+mod dial;
+mod error;
+mod gf2;
+mod graph;
+mod grid;
+mod ilp;
+mod log;
+mod packing;
+mod par;
+mod parser;
+mod report;
+mod shape;
+mod solver;
+mod spatial;
+#[cfg(test)]
+mod testutil;
+mod unionfind;
 
 mod day1;
 mod day2;
@@ -14,82 +46,253 @@ mod day10;
 mod day11;
 mod day12;
 
-fn main() {
-    // Get command line arguments
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() != 4 {
-        panic!("Usage: {} <day num> <part num> <input_file>", args[0]);
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn input_name(path: &Path) -> String {
+    if is_stdin(path) { "<stdin>".to_string() } else { path.display().to_string() }
+}
+
+// read input file, or stdin if path is "-"
+fn read_input(path: &Path) -> Result<String> {
+    let r = if is_stdin(path) {
+        let mut s = String::new();
+        io::stdin().read_to_string(&mut s).map(|_| s)
+    } else {
+        fs::read_to_string(path)
+    };
+    r.map_err(|e| AocError::Io(format!("failed to read {}: {}", input_name(path), e)))
+}
+
+fn list_solvers() {
+    for s in solver::registry() {
+        let params: String = s.params().iter().map(|p| format!(" --{}", p)).collect();
+        println!("{:>2} {} ({} part{}){}", s.day(), s.name(), s.parts(),
+                 if s.parts() > 1 { "s" } else { "" }, params);
     }
+}
 
-    // Read the input file
-    let day = &args[1];
-    let part = &args[2];
-    let input_file_path = &args[3];
-    let content = fs::read_to_string(input_file_path).expect("Failed to read the file");
-
-    // Parse the content and print the result
-    let result = match &day[..] {
-        "1" => match &part[..] {
-            "1" => day1::parse_1(&content),
-            "2" => day1::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "2" => match &part[..] {
-            "1" => day2::parse_1(&content),
-            "2" => day2::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "3" => match &part[..] {
-            "1" => day3::parse_1(&content),
-            "2" => day3::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "4" => match &part[..] {
-            "1" => day4::parse_1(&content),
-            "2" => day4::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "5" => match &part[..] {
-            "1" => day5::parse_1(&content),
-            "2" => day5::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "6" => match &part[..] {
-            "1" => day6::parse_1(&content),
-            "2" => day6::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "7" => match &part[..] {
-            "1" => day7::parse_1(&content),
-            "2" => day7::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "8" => match &part[..] {
-            "1" => day8::parse_1(&content),
-            "2" => day8::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "9" => match &part[..] {
-            "1" => day9::parse_1(&content),
-            "2" => day9::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "10" => match &part[..] {
-            "1" => day10::parse_1(&content),
-            "2" => day10::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "11" => match &part[..] {
-            "1" => day11::parse_1(&content),
-            "2" => day11::parse_2(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        "12" => match &part[..] {
-            "1" => day12::parse_1(&content),
-            _ => panic!("Invalid part {}", part)
-        },
-        _ => panic!("Invalid day {}", day)
+// look for the input of a day in dir, either named dayN.txt or inputN
+fn find_input(dir: &Path, day: u32) -> Option<PathBuf> {
+    [format!("day{}.txt", day), format!("input{}", day)]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn check_answer(answers: Option<&Answers>, day: u32, part: u32, answer: &str) -> Check {
+    answers.map_or(Check::Unknown, |a| a.check(day, part, answer))
+}
+
+// time solver on one input; Ok(None) if the input is invalid, which
+// has been reported already
+fn timed_solve(solver: &dyn Solver, part: u32, input: &Path, content: &str, params: &Params)
+               -> Result<Option<(String, Duration)>> {
+    let start = Instant::now();
+    let result = solver.solve_with(part, content, params);
+    let elapsed = start.elapsed();
+    match result {
+        Ok(r) => Ok(Some((r.to_string(), elapsed))),
+        Err(e @ AocError::Usage(_)) => Err(e),
+        Err(e) => {
+            eprintln!("error: {}: {}", input_name(input), e);
+            Ok(None)
+        }
+    }
+}
+
+// print a result in plain or json format; in plain format, check
+// results go to stderr so the answer is alone on stdout
+fn print_report(format: Format, report: &Report) {
+    match format {
+        Format::Json => println!("{}", report.json()),
+        _ => {
+            println!("{}", report.answer);
+            if let Check::Fail{ expected } = report.check {
+                eprintln!("FAIL day {} part {}: expected {}",
+                          report.day, report.part, expected);
+            }
+        }
+    }
+}
+
+// returns false if any solver failed
+fn run_all(dir: &Path, answers: Option<&Answers>, format: Format) -> Result<bool> {
+    if !dir.is_dir() {
+        return Err(AocError::Io(format!("{} is not a directory", dir.display())));
+    }
+    let text = format == Format::Text;
+    let mut ok = true;
+    if text {
+        println!("{:>3} {:>4} {:>20} {:>12} {:>6}", "day", "part", "answer", "time", "check");
+    }
+    for s in solver::registry() {
+        let Some(path) = find_input(dir, s.day()) else {
+            if text {
+                println!("{:>3} {:>4} {:>20} {:>12} {:>6}", s.day(), "-", "(no input)", "-", "-");
+            }
+            continue;
+        };
+        let content = match read_input(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                if text {
+                    println!("{:>3} {:>4} {:>20} {:>12} {:>6}", s.day(), "-", "(error)", "-", "-");
+                }
+                ok = false;
+                continue;
+            }
+        };
+        for part in 1..=2 {
+            if part > s.parts() {
+                if text {
+                    println!("{:>3} {:>4} {:>20} {:>12} {:>6}",
+                             s.day(), part, "(no such part)", "-", "-");
+                }
+                continue;
+            }
+            let Some((answer, elapsed)) = timed_solve(*s, part, &path, &content, &Params::new())? else {
+                if text {
+                    println!("{:>3} {:>4} {:>20} {:>12} {:>6}",
+                             s.day(), part, "(error)", "-", "-");
+                }
+                ok = false;
+                continue;
+            };
+            let check = check_answer(answers, s.day(), part, &answer);
+            ok &= !check.is_fail();
+            if text {
+                println!("{:>3} {:>4} {:>20} {:>12} {:>6}",
+                         s.day(), part, answer, format!("{:.3?}", elapsed),
+                         check.label());
+            } else {
+                print_report(format, &Report{
+                    day: s.day(), part, input: &input_name(&path),
+                    answer: &answer, elapsed, check: &check,
+                });
+            }
+        }
+    }
+    Ok(ok)
+}
+
+// run solver on each input, printing one answer per input
+fn run_inputs(solver: &dyn Solver, part: u32, inputs: &[PathBuf], params: &Params,
+              answers: Option<&Answers>, format: Format) -> Result<bool> {
+    if inputs.iter().filter(|p| is_stdin(p)).count() > 1 {
+        return Err(AocError::Usage("stdin can only be read once".to_string()));
+    }
+    let mut ok = true;
+    for input in inputs {
+        // an unreadable input fails on its own, like one that does not parse
+        let content = match read_input(input) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ok = false;
+                continue;
+            }
+        };
+
+        // Parse the content and print the result
+        let Some((answer, elapsed)) = timed_solve(solver, part, input, &content, params)? else {
+            ok = false;
+            continue;
+        };
+        let check = check_answer(answers, solver.day(), part, &answer);
+        ok &= !check.is_fail();
+
+        if format != Format::Text {
+            print_report(format, &Report{
+                day: solver.day(), part, input: &input_name(input),
+                answer: &answer, elapsed, check: &check,
+            });
+            continue;
+        }
+
+        // with several inputs, prefix each output line with the input name
+        let prefix = if inputs.len() > 1 { format!("{}: ", input_name(input)) } else { String::new() };
+        println!("{}Parsed result: {}", prefix, answer);
+        match check {
+            Check::Pass => println!("{}PASS", prefix),
+            Check::Fail{ expected } => println!("{}FAIL (expected {})", prefix, expected),
+            Check::Unknown if answers.is_some() =>
+                println!("{}no expected answer for day {} part {}", prefix, solver.day(), part),
+            Check::Unknown => (),
+        }
+    }
+    Ok(ok)
+}
+
+fn run_bench(solver: &dyn Solver, part: u32, input: &Path, runs: usize, params: &Params,
+             format: Format) -> Result<bool> {
+    let content = read_input(input)?;
+    let (answer, stats) = match bench::bench(solver, part, &content, params, runs) {
+        Ok(r) => r,
+        Err(e @ AocError::Usage(_)) => return Err(e),
+        Err(e) => {
+            eprintln!("error: {}: {}", input_name(input), e);
+            return Ok(false);
+        }
+    };
+    let day = solver.day();
+    match format {
+        Format::Text | Format::Plain =>
+            println!("{}", bench::format_plain(day, part, &answer, &stats)),
+        Format::Json => println!("{}", bench::format_json(day, part, &answer, &stats)),
+        Format::Csv => {
+            println!("{}", bench::CSV_HEADER);
+            println!("{}", bench::format_csv(day, part, &answer, &stats));
+        }
+    }
+    Ok(true)
+}
+
+fn lookup_solver(day: u32) -> Result<&'static dyn Solver> {
+    solver::lookup(day)
+        .ok_or_else(|| AocError::Usage(format!("Invalid day {}", day)))
+}
+
+fn run(opts: Options) -> Result<bool> {
+    log::init(opts.verbosity).map_err(AocError::Usage)?;
+    par::set_jobs(opts.jobs).map_err(AocError::Usage)?;
+
+    let answers = match &opts.expect {
+        Some(path) => Some(Answers::parse(&read_input(path)?)
+                           .map_err(|e| AocError::Io(format!("{}: {}", path.display(), e)))?),
+        None => None,
     };
-    println!("Parsed result: {}", result);
+
+    match opts.command {
+        Command::Help(text) => {
+            println!("{}", text);
+            Ok(true)
+        }
+        Command::List => {
+            list_solvers();
+            Ok(true)
+        }
+        Command::All{ inputs } => run_all(&inputs, answers.as_ref(), opts.format),
+        Command::Run{ day, part, inputs } =>
+            run_inputs(lookup_solver(day)?, part, &inputs, &opts.params,
+                       answers.as_ref(), opts.format),
+        Command::Bench{ day, part, input, runs } =>
+            run_bench(lookup_solver(day)?, part, &input, runs, &opts.params, opts.format),
+    }
+}
+
+fn main() -> ExitCode {
+    // Get command line arguments
+    let args: Vec<String> = env::args().collect();
+
+    match cli::parse_args(&args).and_then(run) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,217 @@
+// Polyomino packing: can given counts of pieces fit, without overlap,
+// in a rectangular region?
+//
+// Pieces are stored as row bitmasks of every distinct orientation of
+// their shape. The search takes the first empty cell of the region, in
+// row-major order, and either covers it with the first cell of some
+// piece orientation, or leaves it empty while there is slack (area not
+// needed by the pieces).
+
+use crate::error::{AocError, Result};
+use crate::shape::Shape;
+
+// widest region row the bitmasks can hold
+const MAX_WIDTH: usize = u64::BITS as usize;
+
+// one orientation of a piece: bit x of rows[y] is cell (x, y)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Orientation {
+    rows: Vec<u64>,
+    width: usize,
+    // x of the first cell of the top row
+    anchor: usize,
+}
+
+impl Orientation {
+    fn new(shape: &Shape) -> Orientation {
+        let mut rows: Vec<u64> = vec![0; shape.height()];
+        for &(x, y) in shape.cells() {
+            rows[y] |= 1 << x;
+        }
+        let anchor = rows[0].trailing_zeros() as usize;
+        Orientation{ rows, width: shape.width(), anchor }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Piece {
+    shape: Shape,
+    orientations: Vec<Orientation>,
+}
+
+impl Piece {
+    pub fn new(shape: &Shape) -> Piece {
+        // an empty shape has nothing to place
+        let orientations = if shape.is_empty() {
+            vec![]
+        } else {
+            shape.orientations().iter().map(Orientation::new).collect()
+        };
+        Piece{ shape: shape.clone(), orientations }
+    }
+
+    pub fn cells(&self) -> usize {
+        self.shape.len()
+    }
+}
+
+struct Search<'a> {
+    pieces: &'a [Piece],
+    width: usize,
+    // occupied or given up cells
+    board: Vec<u64>,
+    counts: Vec<usize>,
+    left: usize,
+    slack: usize,
+}
+
+impl Search<'_> {
+    // first empty cell at or after row y
+    fn first_empty(&self, y: usize) -> Option<(usize, usize)> {
+        let full = u64::MAX >> (MAX_WIDTH - self.width);
+        (y..self.board.len())
+            .find(|&y| self.board[y] != full)
+            .map(|y| ((!self.board[y]).trailing_zeros() as usize, y))
+    }
+
+    fn fits(&self, o: &Orientation, x0: usize, y: usize) -> bool {
+        x0 + o.width <= self.width
+            && y + o.rows.len() <= self.board.len()
+            && o.rows.iter().enumerate().all(|(dy, r)| self.board[y + dy] & (r << x0) == 0)
+    }
+
+    fn toggle(&mut self, o: &Orientation, x0: usize, y: usize) {
+        for (dy, r) in o.rows.iter().enumerate() {
+            self.board[y + dy] ^= r << x0;
+        }
+    }
+
+    fn run(&mut self, from_y: usize) -> bool {
+        if self.left == 0 {
+            return true
+        }
+        let Some((x, y)) = self.first_empty(from_y) else {
+            return false
+        };
+
+        // cover (x, y) with the first cell of some piece
+        let pieces = self.pieces;
+        for (k, piece) in pieces.iter().enumerate() {
+            if self.counts[k] == 0 {
+                continue
+            }
+            for o in &piece.orientations {
+                if x < o.anchor || !self.fits(o, x - o.anchor, y) {
+                    continue
+                }
+                self.toggle(o, x - o.anchor, y);
+                self.counts[k] -= 1;
+                self.left -= 1;
+                let done = self.run(y);
+                self.left += 1;
+                self.counts[k] += 1;
+                self.toggle(o, x - o.anchor, y);
+                if done {
+                    return true
+                }
+            }
+        }
+
+        // or leave it empty
+        if self.slack > 0 {
+            self.slack -= 1;
+            self.board[y] |= 1 << x;
+            let done = self.run(y);
+            self.board[y] &= !(1 << x);
+            self.slack += 1;
+            return done
+        }
+        false
+    }
+}
+
+// whether counts[k] copies of every pieces[k] fit in a width x height
+// region, pieces being free to rotate and flip
+pub fn fits(pieces: &[Piece], width: usize, height: usize, counts: &[usize]) -> Result<bool> {
+    assert_eq!(pieces.len(), counts.len());
+    // empty pieces always fit
+    let counts: Vec<usize> = counts.iter()
+        .zip(pieces)
+        .map(|(&c, p)| if p.cells() == 0 { 0 } else { c })
+        .collect();
+    // either may not fit in a usize
+    let needed = counts.iter()
+        .zip(pieces)
+        .try_fold(0usize, |n, (&c, p)| n.checked_add(c.checked_mul(p.cells())?));
+    let area = width.checked_mul(height);
+    let too_large = || AocError::Input(format!("region {}x{} too large to search", width, height));
+    let needed = match (needed, area) {
+        (Some(n), Some(a)) if n > a => return Ok(false),
+        (None, Some(_)) => return Ok(false),
+        (None, None) => return Err(too_large()),
+        (Some(n), _) => n,
+    };
+    // at most needed, so it fits too
+    let total: usize = counts.iter().sum();
+
+    // enough room to give every piece its own bounding box
+    let used = || pieces.iter().zip(&counts).filter(|&(_, &c)| c > 0).map(|(p, _)| p);
+    let box_w = used().map(|p| p.shape.width()).max().unwrap_or(1);
+    let box_h = used().map(|p| p.shape.height()).max().unwrap_or(1);
+    if (width / box_w).checked_mul(height / box_h).is_none_or(|boxes| boxes >= total) {
+        return Ok(true)
+    }
+
+    // rotating the region does not change the answer
+    let (width, height) = if width > height { (height, width) } else { (width, height) };
+    let Some(area) = area.filter(|_| width <= MAX_WIDTH) else {
+        return Err(too_large())
+    };
+
+    let mut search = Search{ pieces, width, board: vec![0; height], counts, left: total,
+                             slack: area - needed };
+    Ok(search.run(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(rows: &[&str]) -> Piece {
+        Piece::new(&Shape::parse(rows))
+    }
+
+    #[test]
+    fn test_orientations() {
+        let p = piece(&["#.", "##"]);
+        assert_eq!(p.orientations.len(), 4);
+        assert_eq!(p.orientations[0].rows, [0b01, 0b11]);
+        assert_eq!(p.orientations.iter().map(|o| o.anchor).collect::<Vec<_>>(), [0, 0, 0, 1]);
+        assert!(piece(&["..."]).orientations.is_empty());
+    }
+    #[test]
+    fn test_fits() {
+        let l = piece(&["#.", "##"]);
+        let bar = piece(&["###"]);
+        let pieces = [l, bar];
+        // two L trominoes tile a 2x3 rectangle, in either direction
+        assert_eq!(fits(&pieces, 2, 3, &[2, 0]), Ok(true));
+        assert_eq!(fits(&pieces, 3, 2, &[2, 0]), Ok(true));
+        // area is enough, shape is not
+        assert_eq!(fits(&pieces, 1, 6, &[2, 0]), Ok(false));
+        assert_eq!(fits(&pieces, 3, 3, &[1, 2]), Ok(false));
+        assert_eq!(fits(&pieces, 3, 4, &[2, 2]), Ok(true));
+        assert_eq!(fits(&pieces, 2, 2, &[0, 1]), Ok(false));
+        assert_eq!(fits(&pieces, 2, 2, &[0, 0]), Ok(true));
+    }
+    #[test]
+    fn test_fits_large() {
+        let pieces = [piece(&["#.", "##"])];
+        assert_eq!(fits(&pieces, 99999999999, 99999999999, &[1]), Ok(true));
+        assert_eq!(fits(&pieces, 1 << 40, 1 << 40, &[usize::MAX / 4]), Ok(true));
+        // more cells needed than a usize can count
+        assert_eq!(fits(&pieces, 2, 3, &[usize::MAX]), Ok(false));
+        assert!(fits(&pieces, 1 << 40, 1 << 40, &[usize::MAX]).is_err());
+        assert!(fits(&pieces, 100, 100, &[3000]).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+// Optional parallel execution of independent per-record work
+//
+// With the "parallel" feature and --jobs N > 1, map() spreads work
+// over a pool of N threads; otherwise it runs serially, which is the
+// default. Results always come back in input order, so combining them
+// is deterministic either way.
+
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "parallel")]
+static JOBS: AtomicUsize = AtomicUsize::new(1);
+
+#[cfg(feature = "parallel")]
+pub fn set_jobs(n: usize) -> Result<(), String> {
+    if n > 1 {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(n)
+            .build_global()
+            .map_err(|e| format!("failed to start {} jobs: {}", n, e))?;
+    }
+    JOBS.store(n, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn set_jobs(n: usize) -> Result<(), String> {
+    if n > 1 {
+        return Err("--jobs requires building with the \"parallel\" feature".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parallel")]
+fn jobs() -> usize {
+    JOBS.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "parallel")]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where T: Sync, R: Send, F: Fn(&T) -> R + Sync + Send {
+    use rayon::prelude::*;
+
+    if jobs() > 1 {
+        items.par_iter().map(f).collect()
+    } else {
+        items.iter().map(f).collect()
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where F: Fn(&T) -> R {
+    items.iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_order() {
+        let v: Vec<u64> = (0..1000).collect();
+        assert_eq!(map(&v, |n| n * n), v.iter().map(|n| n * n).collect::<Vec<u64>>());
+    }
+}
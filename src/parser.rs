@@ -0,0 +1,407 @@
+// Small parser combinators for the puzzle input formats
+//
+// A parser is a function from an Input (position within a line) to
+// either a value and the remaining input, or an AocError pointing at
+// the offending line, column and text. Puzzle formats are declared once
+// by combining the primitives below, e.g. a day2 line is
+// many1(terminated(range(uint()), opt(tag(",")))).
+
+use std::str::FromStr;
+
+use crate::error::{numbered_lines, AocError, Result};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Input<'a> {
+    line_num: usize,
+    line: &'a str,
+    // byte offset within line
+    pos: usize,
+}
+
+pub type PResult<'a, T> = Result<(T, Input<'a>)>;
+
+pub trait Parser<'a, T>: Fn(Input<'a>) -> PResult<'a, T> {}
+
+impl<'a, T, F> Parser<'a, T> for F where F: Fn(Input<'a>) -> PResult<'a, T> {}
+
+impl<'a> Input<'a> {
+    pub fn new(line_num: usize, line: &'a str) -> Input<'a> {
+        Input{ line_num, line, pos: 0 }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.line[self.pos..]
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos == self.line.len()
+    }
+
+    fn advance(&self, n: usize) -> Input<'a> {
+        Input{ pos: self.pos + n, ..*self }
+    }
+
+    // error about the word starting at the current position
+    pub fn error(&self, msg: &str) -> AocError {
+        let rest = self.rest();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        self.error_len(end, msg)
+    }
+
+    // error about the next len bytes
+    fn error_len(&self, len: usize, msg: &str) -> AocError {
+        AocError::parse(self.line_num, self.pos + 1, &self.rest()[..len], msg)
+    }
+}
+
+// column of an error, for choosing the one that got further
+fn error_column(e: &AocError) -> usize {
+    match e {
+        AocError::Parse{ column, .. } => *column,
+        _ => 0,
+    }
+}
+
+// whether parsing went past input, leading spaces aside, before
+// failing with e
+fn consumed(input: &Input, e: &AocError) -> bool {
+    let rest = input.rest();
+    let skip = rest.len() - rest.trim_start().len();
+    error_column(e) > input.pos + skip + 1
+}
+
+// -- primitives
+
+pub fn tag<'a>(t: &'static str) -> impl Parser<'a, &'a str> {
+    move |i: Input<'a>| {
+        if i.rest().starts_with(t) {
+            Ok((&i.rest()[..t.len()], i.advance(t.len())))
+        } else {
+            Err(i.error(&format!("expected {:?}", t)))
+        }
+    }
+}
+
+// one character among chars
+pub fn one_of<'a>(chars: &'static str) -> impl Parser<'a, char> {
+    move |i: Input<'a>| match i.rest().chars().next() {
+        Some(c) if chars.contains(c) => Ok((c, i.advance(c.len_utf8()))),
+        _ => Err(i.error(&format!("expected one of {:?}", chars))),
+    }
+}
+
+// non-empty run of characters satisfying pred; what names them for errors
+pub fn take_while1<'a, F>(pred: F, what: &'static str) -> impl Parser<'a, &'a str>
+where F: Fn(char) -> bool {
+    move |i: Input<'a>| {
+        let rest = i.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        if len == 0 {
+            Err(i.error(&format!("expected {}", what)))
+        } else {
+            Ok((&rest[..len], i.advance(len)))
+        }
+    }
+}
+
+fn number<'a, T: FromStr>(i: Input<'a>, sign: bool) -> PResult<'a, T> {
+    let rest = i.rest();
+    let start = if sign && rest.starts_with('-') { 1 } else { 0 };
+    let len = rest[start..].find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len() - start);
+    if len == 0 {
+        return Err(i.error("expected number"));
+    }
+    let digits = &rest[..start + len];
+    let n = digits.parse()
+        .map_err(|_| i.error_len(digits.len(), "number out of range"))?;
+    Ok((n, i.advance(digits.len())))
+}
+
+// unsigned decimal integer
+pub fn uint<'a, T: FromStr>() -> impl Parser<'a, T> {
+    |i| number(i, false)
+}
+
+// decimal integer with optional minus sign
+pub fn int<'a, T: FromStr>() -> impl Parser<'a, T> {
+    |i| number(i, true)
+}
+
+// alphanumeric label, e.g. a device name
+pub fn label<'a>() -> impl Parser<'a, &'a str> {
+    take_while1(|c| c.is_ascii_alphanumeric() || c == '_', "label")
+}
+
+// zero or more spaces
+pub fn spaces<'a>() -> impl Parser<'a, ()> {
+    |i: Input<'a>| {
+        let rest = i.rest();
+        let len = rest.find(|c| c != ' ' && c != '\t').unwrap_or(rest.len());
+        Ok(((), i.advance(len)))
+    }
+}
+
+// -- combinators
+
+pub fn map<'a, T, U, P, F>(p: P, f: F) -> impl Parser<'a, U>
+where P: Parser<'a, T>, F: Fn(T) -> U {
+    move |i| p(i).map(|(v, rest)| (f(v), rest))
+}
+
+// fail with msg, at the start of p, if the value parsed by p does not
+// satisfy pred
+pub fn verify<'a, T, P, F>(p: P, pred: F, msg: &'static str) -> impl Parser<'a, T>
+where P: Parser<'a, T>, F: Fn(&T) -> bool {
+    move |i: Input<'a>| {
+        let (v, rest) = p(i)?;
+        if pred(&v) {
+            Ok((v, rest))
+        } else {
+            Err(i.error_len(rest.pos - i.pos, msg))
+        }
+    }
+}
+
+pub fn pair<'a, A, B, PA, PB>(pa: PA, pb: PB) -> impl Parser<'a, (A, B)>
+where PA: Parser<'a, A>, PB: Parser<'a, B> {
+    move |i| {
+        let (a, i) = pa(i)?;
+        let (b, i) = pb(i)?;
+        Ok(((a, b), i))
+    }
+}
+
+pub fn preceded<'a, A, B, PA, PB>(pa: PA, pb: PB) -> impl Parser<'a, B>
+where PA: Parser<'a, A>, PB: Parser<'a, B> {
+    map(pair(pa, pb), |(_, b)| b)
+}
+
+pub fn terminated<'a, A, B, PA, PB>(pa: PA, pb: PB) -> impl Parser<'a, A>
+where PA: Parser<'a, A>, PB: Parser<'a, B> {
+    map(pair(pa, pb), |(a, _)| a)
+}
+
+pub fn separated_pair<'a, A, S, B, PA, PS, PB>(pa: PA, sep: PS, pb: PB) -> impl Parser<'a, (A, B)>
+where PA: Parser<'a, A>, PS: Parser<'a, S>, PB: Parser<'a, B> {
+    pair(terminated(pa, sep), pb)
+}
+
+pub fn delimited<'a, O, T, C, PO, P, PC>(open: PO, p: P, close: PC) -> impl Parser<'a, T>
+where PO: Parser<'a, O>, P: Parser<'a, T>, PC: Parser<'a, C> {
+    preceded(open, terminated(p, close))
+}
+
+// p preceded by optional spaces
+pub fn ws<'a, T, P>(p: P) -> impl Parser<'a, T>
+where P: Parser<'a, T> {
+    preceded(spaces(), p)
+}
+
+// None if p fails without consuming input
+pub fn opt<'a, T, P>(p: P) -> impl Parser<'a, Option<T>>
+where P: Parser<'a, T> {
+    move |i| match p(i) {
+        Ok((v, rest)) => Ok((Some(v), rest)),
+        Err(e) if consumed(&i, &e) => Err(e),
+        Err(_) => Ok((None, i)),
+    }
+}
+
+// first of pa or pb that succeeds; if both fail, the error that got
+// further into the input
+pub fn alt<'a, T, PA, PB>(pa: PA, pb: PB) -> impl Parser<'a, T>
+where PA: Parser<'a, T>, PB: Parser<'a, T> {
+    move |i| match pa(i) {
+        Ok(r) => Ok(r),
+        Err(ea) => match pb(i) {
+            Ok(r) => Ok(r),
+            Err(eb) if error_column(&eb) > error_column(&ea) => Err(eb),
+            Err(_) => Err(ea),
+        },
+    }
+}
+
+// zero or more p, stopping at the first one that fails without
+// consuming input
+pub fn many0<'a, T, P>(p: P) -> impl Parser<'a, Vec<T>>
+where P: Parser<'a, T> {
+    move |mut i| {
+        let mut v = vec![];
+        loop {
+            match p(i) {
+                Ok((e, rest)) if rest.pos > i.pos => {
+                    v.push(e);
+                    i = rest;
+                }
+                Ok(_) => return Ok((v, i)),
+                Err(e) if consumed(&i, &e) => return Err(e),
+                Err(_) => return Ok((v, i)),
+            }
+        }
+    }
+}
+
+pub fn many1<'a, T, P>(p: P) -> impl Parser<'a, Vec<T>>
+where P: Parser<'a, T> {
+    move |i| {
+        let (first, i) = p(i)?;
+        let (mut rest, i) = many0(&p)(i)?;
+        rest.insert(0, first);
+        Ok((rest, i))
+    }
+}
+
+// one or more p, separated by sep
+pub fn sep_by1<'a, T, S, P, PS>(p: P, sep: PS) -> impl Parser<'a, Vec<T>>
+where P: Parser<'a, T>, PS: Parser<'a, S> {
+    move |i| {
+        let (first, i) = p(i)?;
+        let (mut rest, i) = many0(preceded(&sep, &p))(i)?;
+        rest.insert(0, first);
+        Ok((rest, i))
+    }
+}
+
+// -- puzzle formats
+
+// comma-separated list, e.g. 1,2,3
+pub fn comma_list<'a, T, P>(p: P) -> impl Parser<'a, Vec<T>>
+where P: Parser<'a, T> {
+    sep_by1(p, tag(","))
+}
+
+// inclusive range, e.g. 11-22
+pub fn range<'a, T, P>(p: P) -> impl Parser<'a, (T, T)>
+where P: Parser<'a, T> {
+    move |i| separated_pair(&p, tag("-"), &p)(i)
+}
+
+// [..]
+pub fn brackets<'a, T, P>(p: P) -> impl Parser<'a, T>
+where P: Parser<'a, T> {
+    delimited(tag("["), p, tag("]"))
+}
+
+// (..)
+pub fn parens<'a, T, P>(p: P) -> impl Parser<'a, T>
+where P: Parser<'a, T> {
+    delimited(tag("("), p, tag(")"))
+}
+
+// {..}
+pub fn braces<'a, T, P>(p: P) -> impl Parser<'a, T>
+where P: Parser<'a, T> {
+    delimited(tag("{"), p, tag("}"))
+}
+
+// key: values
+pub fn labelled<'a, K, V, PK, PV>(key: PK, values: PV) -> impl Parser<'a, (K, V)>
+where PK: Parser<'a, K>, PV: Parser<'a, V> {
+    separated_pair(key, pair(tag(":"), spaces()), values)
+}
+
+// -- entry points
+
+// parse a whole line (but trailing whitespace) with p
+pub fn parse_line<'a, T, P>(line_num: usize, line: &'a str, p: P) -> Result<T>
+where P: Parser<'a, T> {
+    let (v, rest) = p(Input::new(line_num, line.trim_end()))?;
+    let (_, rest) = spaces()(rest)?;
+    if rest.at_end() {
+        Ok(v)
+    } else {
+        Err(rest.error("unexpected text"))
+    }
+}
+
+// parse every non-empty line of text with p
+pub fn parse_lines<'a, T, P>(text: &'a str, p: P) -> Result<Vec<T>>
+where P: Parser<'a, T> {
+    numbered_lines(text)
+        .map(|(n, line)| parse_line(n, line, &p))
+        .collect()
+}
+
+// sections of consecutive non-empty lines, separated by blank lines,
+// with their line numbers
+pub fn sections(text: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut sections = vec![];
+    let mut cur = vec![];
+    for (k, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !cur.is_empty() {
+                sections.push(std::mem::take(&mut cur));
+            }
+        } else {
+            cur.push((k + 1, line));
+        }
+    }
+    if !cur.is_empty() {
+        sections.push(cur);
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(parse_line(1, "42", uint::<u32>()), Ok(42));
+        assert_eq!(parse_line(1, "-42", int::<i64>()), Ok(-42));
+        assert_eq!(parse_line(2, "-42", uint::<u32>()),
+                   Err(AocError::parse(2, 1, "-42", "expected number")));
+        assert_eq!(parse_line(3, "x 300", preceded(tag("x "), uint::<u8>())),
+                   Err(AocError::parse(3, 3, "300", "number out of range")));
+        assert_eq!(parse_line(4, "12ab", uint::<u32>()),
+                   Err(AocError::parse(4, 3, "ab", "unexpected text")));
+    }
+    #[test]
+    fn test_lists_and_ranges() {
+        let p = comma_list(range(uint::<u64>()));
+        assert_eq!(parse_line(1, "11-22,95-115", &p), Ok(vec![(11, 22), (95, 115)]));
+        assert_eq!(parse_line(1, "11-22,95+115", &p),
+                   Err(AocError::parse(1, 9, "+115", "expected \"-\"")));
+    }
+    #[test]
+    fn test_groups() {
+        let p = pair(brackets(many1(one_of(".#"))),
+                     pair(many0(ws(parens(comma_list(uint::<usize>())))),
+                          ws(braces(comma_list(uint::<usize>())))));
+        assert_eq!(parse_line(1, "[.#] (1) (0,1) {3,5}", &p),
+                   Ok((vec!['.', '#'], (vec![vec![1], vec![0, 1]], vec![3, 5]))));
+        // errors inside a repeated group are reported where they occur
+        assert_eq!(parse_line(1, "[.#] (1) (0,x) {3,5}", &p),
+                   Err(AocError::parse(1, 13, "x)", "expected number")));
+    }
+    #[test]
+    fn test_labelled() {
+        let p = labelled(label(), many1(ws(label())));
+        assert_eq!(parse_line(1, "you: bbb ccc", &p), Ok(("you", vec!["bbb", "ccc"])));
+        assert_eq!(parse_line(1, "you bbb", &p),
+                   Err(AocError::parse(1, 4, "", "expected \":\"")));
+    }
+    #[test]
+    fn test_alt_verify_opt() {
+        let p = alt(map(uint::<u32>(), Some), map(tag("-"), |_| None));
+        assert_eq!(parse_lines("1\n-\n", &p), Ok(vec![Some(1), None]));
+        assert_eq!(parse_line(1, "x", &p),
+                   Err(AocError::parse(1, 1, "x", "expected number")));
+
+        let p = verify(uint::<u32>(), |&n| n < 10, "too big");
+        assert_eq!(parse_line(1, "12", &p), Err(AocError::parse(1, 1, "12", "too big")));
+
+        let p = pair(opt(tag("+")), uint::<u32>());
+        assert_eq!(parse_line(1, "+3", &p), Ok((Some("+"), 3)));
+        assert_eq!(parse_line(1, "3", &p), Ok((None, 3)));
+    }
+    #[test]
+    fn test_sections() {
+        let s = sections("\n0:\n#.\n\n1:\n.#\n\n\n4x4: 1 2\n");
+        assert_eq!(s, [vec![(2, "0:"), (3, "#.")],
+                       vec![(5, "1:"), (6, ".#")],
+                       vec![(9, "4x4: 1 2")]]);
+    }
+}
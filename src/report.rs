@@ -0,0 +1,65 @@
+// Rendering of solver results in machine-readable formats
+
+use std::time::Duration;
+
+use crate::answers::Check;
+
+pub struct Report<'a> {
+    pub day: u32,
+    pub part: u32,
+    pub input: &'a str,
+    pub answer: &'a str,
+    pub elapsed: Duration,
+    pub check: &'a Check,
+}
+
+impl Report<'_> {
+    pub fn json(&self) -> String {
+        let check = match self.check {
+            Check::Unknown => String::new(),
+            c => format!(",\"check\":{}", json_string(c.label())),
+        };
+        format!("{{\"day\":{},\"part\":{},\"input\":{},\"answer\":{},\"elapsed_ns\":{}{}}}",
+                self.day, self.part, json_string(self.input), json_string(self.answer),
+                self.elapsed.as_nanos(), check)
+    }
+}
+
+// s as a quoted JSON string
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("abc"), "\"abc\"");
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+    #[test]
+    fn test_report_json() {
+        let r = Report{ day: 1, part: 2, input: "in/day1.txt", answer: "5847",
+                        elapsed: Duration::from_micros(12), check: &Check::Unknown };
+        assert_eq!(r.json(),
+                   "{\"day\":1,\"part\":2,\"input\":\"in/day1.txt\",\"answer\":\"5847\",\"elapsed_ns\":12000}");
+        let r = Report{ check: &Check::Pass, ..r };
+        assert!(r.json().ends_with(",\"elapsed_ns\":12000,\"check\":\"PASS\"}"));
+    }
+}
@@ -0,0 +1,156 @@
+// Polyomino shapes: a set of cells, normalised so that the smallest x
+// and y are 0, with the 8 rotations and reflections of the square
+
+use std::fmt;
+
+use crate::grid::Pos;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    // sorted by (y, x), i.e. row-major
+    cells: Vec<Pos>,
+    width: usize,
+    height: usize,
+}
+
+impl Shape {
+    // from any cells, moved to the origin
+    pub fn from_cells<I: IntoIterator<Item = (i64, i64)>>(cells: I) -> Shape {
+        let cells: Vec<(i64, i64)> = cells.into_iter().collect();
+        let x0 = cells.iter().map(|c| c.0).min().unwrap_or(0);
+        let y0 = cells.iter().map(|c| c.1).min().unwrap_or(0);
+        let mut cells: Vec<Pos> = cells.iter()
+            .map(|&(x, y)| ((x - x0) as usize, (y - y0) as usize))
+            .collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        Shape{ cells, width, height }
+    }
+
+    // from rows of '#' (cell) and anything else (hole)
+    pub fn parse<S: AsRef<str>>(rows: &[S]) -> Shape {
+        Shape::from_cells(rows.iter()
+                          .enumerate()
+                          .flat_map(|(y, row)| row.as_ref()
+                                    .chars()
+                                    .enumerate()
+                                    .filter(|&(_, c)| c == '#')
+                                    .map(move |(x, _)| (x as i64, y as i64))))
+    }
+
+    pub fn cells(&self) -> &[Pos] {
+        &self.cells
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // bounding box
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        self.cells.binary_search_by_key(&(p.1, p.0), |&(x, y)| (y, x)).is_ok()
+    }
+
+    fn map<F: Fn(i64, i64) -> (i64, i64)>(&self, f: F) -> Shape {
+        Shape::from_cells(self.cells.iter().map(|&(x, y)| f(x as i64, y as i64)))
+    }
+
+    // quarter turn clockwise
+    pub fn rotate(&self) -> Shape {
+        self.map(|x, y| (-y, x))
+    }
+
+    // mirror image, left to right
+    pub fn flip(&self) -> Shape {
+        self.map(|x, y| (-x, y))
+    }
+
+    // the 4 rotations of the shape, then those of its mirror image
+    pub fn transforms(&self) -> Vec<Shape> {
+        let mut v = vec![self.clone()];
+        for k in 1..8 {
+            v.push(if k == 4 { self.flip() } else { v[k - 1].rotate() });
+        }
+        v
+    }
+
+    // distinct transforms, e.g. 1 for a square, 8 for an L
+    pub fn orientations(&self) -> Vec<Shape> {
+        let mut v = self.transforms();
+        v.sort();
+        v.dedup();
+        v
+    }
+
+    // same for every transform of the shape
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn canonical(&self) -> Shape {
+        self.transforms().into_iter().min().unwrap()
+    }
+
+    // rows of '#' and '.', e.g. for debug logs
+    pub fn render(&self) -> String {
+        (0..self.height)
+            .map(|y| (0..self.width)
+                 .map(|x| if self.contains((x, y)) { '#' } else { '.' })
+                 .collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_render() {
+        let s = Shape::parse(&["...", ".##", ".#."]);
+        assert_eq!((s.width(), s.height(), s.len()), (2, 2, 3));
+        assert_eq!(s.cells(), [(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(s.to_string(), "##\n#.");
+        assert!(s.contains((1, 0)));
+        assert!(!s.contains((1, 1)));
+        assert!(Shape::parse(&["..."]).is_empty());
+    }
+    #[test]
+    fn test_transforms() {
+        let s = Shape::parse(&["###", "#.."]);
+        assert_eq!(s.rotate().render(), "##\n.#\n.#");
+        assert_eq!(s.flip().render(), "###\n..#");
+        assert_eq!(s.rotate().rotate().rotate().rotate(), s);
+        assert_eq!(s.transforms().len(), 8);
+        assert_eq!(s.orientations().len(), 8);
+        assert_eq!(Shape::parse(&["##", "##"]).orientations().len(), 1);
+        assert_eq!(Shape::parse(&["##.", ".##"]).orientations().len(), 4);
+        assert_eq!(Shape::parse(&["###"]).orientations().len(), 2);
+    }
+    #[test]
+    fn test_canonical() {
+        let s = Shape::parse(&["##.", ".##"]);
+        for t in s.transforms() {
+            assert_eq!(t.canonical(), s.canonical());
+        }
+        assert_ne!(s.canonical(), Shape::parse(&["###"]).canonical());
+    }
+}
@@ -0,0 +1,176 @@
+// Common interface to the daily puzzles, and the registry of all
+// available solvers
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::answer::Answer;
+use crate::error::{AocError, Result};
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12};
+
+// solver-specific settings from the command line, e.g. --from you
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Params {
+        Default::default()
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.values.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|v| &v[..])
+    }
+
+    // comma-separated values, e.g. --via fft,dac
+    pub fn list(&self, name: &str) -> Option<Vec<&str>> {
+        self.get(name).map(|v| v.split(',').filter(|s| !s.is_empty()).collect())
+    }
+
+    // comma-separated numbers, e.g. --dial-size 100,60
+    pub fn list_of<T: FromStr>(&self, name: &str) -> Result<Option<Vec<T>>> {
+        self.list(name).map(|v| v.into_iter()
+                            .map(|s| s.parse().map_err(|_| AocError::Usage(
+                                format!("invalid value for --{}: {}", name, s))))
+                            .collect())
+            .transpose()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(|k| &k[..])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+pub trait Solver: Sync {
+    // short identifier, e.g. "day1"
+    fn name(&self) -> &'static str;
+    fn day(&self) -> u32;
+    // number of parts, numbered from 1
+    fn parts(&self) -> u32;
+    // names of the parameters the solver takes
+    fn params(&self) -> &'static [&'static str] {
+        &[]
+    }
+    fn solve_with(&self, part: u32, text: &str, params: &Params) -> Result<Answer>;
+    // with no parameters
+    #[cfg(test)]
+    fn solve(&self, part: u32, text: &str) -> Result<Answer> {
+        self.solve_with(part, text, &Params::new())
+    }
+}
+
+// solver made of one plain function per part, which is what every
+// day module provides
+pub struct DaySolver {
+    name: &'static str,
+    day: u32,
+    parts: &'static [fn(&str) -> Result<Answer>],
+    // parts taking parameters, replacing the plain ones, and the names
+    // of the parameters they take
+    param_parts: &'static [fn(&str, &Params) -> Result<Answer>],
+    param_names: &'static [&'static str],
+}
+
+impl DaySolver {
+    pub const fn new(name: &'static str, day: u32,
+                     parts: &'static [fn(&str) -> Result<Answer>]) -> DaySolver {
+        DaySolver{ name, day, parts, param_parts: &[], param_names: &[] }
+    }
+
+    pub const fn with_params(self, names: &'static [&'static str],
+                             parts: &'static [fn(&str, &Params) -> Result<Answer>]) -> DaySolver {
+        DaySolver{ param_parts: parts, param_names: names, ..self }
+    }
+}
+
+impl Solver for DaySolver {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    fn day(&self) -> u32 {
+        self.day
+    }
+    fn parts(&self) -> u32 {
+        self.parts.len() as u32
+    }
+    fn params(&self) -> &'static [&'static str] {
+        self.param_names
+    }
+    fn solve_with(&self, part: u32, text: &str, params: &Params) -> Result<Answer> {
+        if let Some(name) = params.names().find(|n| !self.param_names.contains(n)) {
+            return Err(AocError::Usage(format!("{} does not take --{}", self.name, name)));
+        }
+        let k = (part as usize).checked_sub(1)
+            .filter(|&k| k < self.parts.len())
+            .ok_or_else(|| AocError::Usage(
+                format!("{} has no part {}", self.name, part)))?;
+        match self.param_parts.get(k) {
+            Some(f) => f(text, params),
+            None => (self.parts[k])(text),
+        }
+    }
+}
+
+// every day module registers its solver here, in day order
+static SOLVERS: &[&dyn Solver] = &[
+    &day1::SOLVER,
+    &day2::SOLVER,
+    &day3::SOLVER,
+    &day4::SOLVER,
+    &day5::SOLVER,
+    &day6::SOLVER,
+    &day7::SOLVER,
+    &day8::SOLVER,
+    &day9::SOLVER,
+    &day10::SOLVER,
+    &day11::SOLVER,
+    &day12::SOLVER,
+];
+
+pub fn registry() -> &'static [&'static dyn Solver] {
+    SOLVERS
+}
+
+pub fn lookup(day: u32) -> Option<&'static dyn Solver> {
+    SOLVERS.iter().find(|s| s.day() == day).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let days: Vec<u32> = registry().iter().map(|s| s.day()).collect();
+        assert_eq!(days, (1..=12).collect::<Vec<u32>>());
+        assert!(registry().iter().all(|s| s.name() == format!("day{}", s.day())));
+    }
+    #[test]
+    fn test_lookup() {
+        let s = lookup(12).unwrap();
+        assert_eq!(s.parts(), 1);
+        assert_eq!(s.solve(2, ""),
+                   Err(AocError::Usage("day12 has no part 2".to_string())));
+        assert!(s.solve(0, "").is_err());
+        assert!(lookup(13).is_none());
+        assert_eq!(lookup(1).unwrap().solve(1, "R50\n"), Ok(Answer::from(1u32)));
+    }
+    #[test]
+    fn test_params() {
+        let mut params = Params::new();
+        params.set("via", "fft,dac");
+        assert_eq!(params.list("via"), Some(vec!["fft", "dac"]));
+        assert_eq!(params.get("from"), None);
+        assert_eq!(lookup(3).unwrap().solve_with(1, "", &params),
+                   Err(AocError::Usage("day3 does not take --via".to_string())));
+    }
+}
@@ -0,0 +1,268 @@
+// k-d tree over 3D points, streaming pairs of points by increasing
+// distance without materialising all n(n-1)/2 of them
+//
+// For every point i, the tree answers "next nearest j > i after
+// (dist, j)"; a heap holding one such candidate per point then yields
+// pairs in (dist, i, j) order.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+pub type Point = (i64, i64, i64);
+
+// points per leaf
+const LEAF_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pair {
+    // squared distance
+    pub dist: i64,
+    pub i: usize,
+    pub j: usize,
+}
+
+fn coord(p: Point, axis: usize) -> i64 {
+    match axis {
+        0 => p.0,
+        1 => p.1,
+        _ => p.2,
+    }
+}
+
+// squared euclidean distance
+pub fn distance(a: Point, b: Point) -> i64 {
+    (a.0 - b.0).pow(2) + (a.1 - b.1).pow(2) + (a.2 - b.2).pow(2)
+}
+
+struct Node {
+    // bounding box
+    min: [i64; 3],
+    max: [i64; 3],
+    // largest point index below this node
+    max_index: usize,
+    // range of KdTree::order for leaves, children for inner nodes
+    start: usize,
+    end: usize,
+    children: Option<(usize, usize)>,
+}
+
+impl Node {
+    // smallest and largest squared distance from p to the box
+    fn dist_bounds(&self, p: Point) -> (i64, i64) {
+        let (mut lo, mut hi) = (0, 0);
+        for axis in 0..3 {
+            let c = coord(p, axis);
+            let (a, b) = (self.min[axis], self.max[axis]);
+            if c < a {
+                lo += (a - c).pow(2);
+            } else if c > b {
+                lo += (c - b).pow(2);
+            }
+            hi += (c - a).abs().max((c - b).abs()).pow(2);
+        }
+        (lo, hi)
+    }
+}
+
+pub struct KdTree<'a> {
+    points: &'a [Point],
+    // point indices, grouped by leaf
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl<'a> KdTree<'a> {
+    pub fn new(points: &'a [Point]) -> KdTree<'a> {
+        let mut tree = KdTree{ points, order: (0..points.len()).collect(), nodes: vec![] };
+        if !points.is_empty() {
+            tree.build(0, points.len());
+        }
+        tree
+    }
+
+    // build the node for order[start..end], return its index
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut min = [i64::MAX; 3];
+        let mut max = [i64::MIN; 3];
+        for &k in &self.order[start..end] {
+            for axis in 0..3 {
+                let c = coord(self.points[k], axis);
+                min[axis] = min[axis].min(c);
+                max[axis] = max[axis].max(c);
+            }
+        }
+        let max_index = *self.order[start..end].iter().max().unwrap();
+
+        let id = self.nodes.len();
+        self.nodes.push(Node{ min, max, max_index, start, end, children: None });
+        if end - start > LEAF_SIZE {
+            // split at the median of the widest axis
+            let axis = (0..3).max_by_key(|&a| max[a] - min[a]).unwrap();
+            let mid = (start + end) / 2;
+            let points = self.points;
+            self.order[start..end]
+                .select_nth_unstable_by_key(mid - start, |&k| coord(points[k], axis));
+            let left = self.build(start, mid);
+            let right = self.build(mid, end);
+            self.nodes[id].children = Some((left, right));
+        }
+        id
+    }
+
+    // up to k nearest points j > i whose (dist, j) come strictly after
+    // `after`, closest first
+    pub fn next_neighbours(&self, i: usize, after: Option<(i64, usize)>, k: usize)
+                           -> Vec<(i64, usize)> {
+        // max-heap of the k best candidates so far
+        let mut best = BinaryHeap::new();
+        if !self.nodes.is_empty() && k > 0 {
+            self.search(0, i, after, k, &mut best);
+        }
+        best.into_sorted_vec()
+    }
+
+    fn search(&self, id: usize, i: usize, after: Option<(i64, usize)>, k: usize,
+              best: &mut BinaryHeap<(i64, usize)>) {
+        let node = &self.nodes[id];
+        if node.max_index <= i {
+            return
+        }
+        let p = self.points[i];
+        let (lo, hi) = node.dist_bounds(p);
+        // everything is further than the k best candidates so far
+        if best.len() == k && best.peek().is_some_and(|&(d, _)| lo > d) {
+            return
+        }
+        // everything is closer than what was already returned
+        if after.is_some_and(|(d, _)| hi < d) {
+            return
+        }
+
+        match node.children {
+            None => {
+                for &j in &self.order[node.start..node.end] {
+                    if j <= i {
+                        continue
+                    }
+                    let key = (distance(p, self.points[j]), j);
+                    if after.is_some_and(|a| key <= a) {
+                        continue
+                    }
+                    if best.len() < k {
+                        best.push(key);
+                    } else if best.peek().is_some_and(|&b| key < b) {
+                        best.pop();
+                        best.push(key);
+                    }
+                }
+            }
+            Some((left, right)) => {
+                // closer child first, to tighten best early
+                let (a, b) = if self.nodes[left].dist_bounds(p).0
+                    <= self.nodes[right].dist_bounds(p).0 {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                self.search(a, i, after, k, best);
+                self.search(b, i, after, k, best);
+            }
+        }
+    }
+
+    // all pairs i < j, lazily, in (dist, i, j) order
+    pub fn pairs(&self) -> Pairs<'_, 'a> {
+        let mut pairs = Pairs{ tree: self, pending: vec![vec![]; self.points.len()],
+                               heap: BinaryHeap::new() };
+        for i in 0..self.points.len() {
+            pairs.refill(i, None);
+        }
+        pairs
+    }
+}
+
+// neighbours fetched per tree search
+const BATCH: usize = 16;
+
+pub struct Pairs<'t, 'a> {
+    tree: &'t KdTree<'a>,
+    // upcoming neighbours of every point, furthest first
+    pending: Vec<Vec<(i64, usize)>>,
+    // next pair of every point that has one left
+    heap: BinaryHeap<Reverse<Pair>>,
+}
+
+impl Pairs<'_, '_> {
+    // queue the next pair of point i, after pair (i, last) if any
+    fn refill(&mut self, i: usize, last: Option<(i64, usize)>) {
+        if self.pending[i].is_empty() {
+            let mut v = self.tree.next_neighbours(i, last, BATCH);
+            v.reverse();
+            self.pending[i] = v;
+        }
+        if let Some((dist, j)) = self.pending[i].pop() {
+            self.heap.push(Reverse(Pair{ dist, i, j }));
+        }
+    }
+}
+
+impl Iterator for Pairs<'_, '_> {
+    type Item = Pair;
+
+    fn next(&mut self) -> Option<Pair> {
+        let Reverse(p) = self.heap.pop()?;
+        self.refill(p.i, Some((p.dist, p.j)));
+        Some(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    // every pair, sorted the slow way
+    fn all_pairs(points: &[Point]) -> Vec<Pair> {
+        let n = points.len();
+        let mut v: Vec<Pair> = (0..n)
+            .flat_map(|i| (i+1..n).map(move |j| (i, j)))
+            .map(|(i, j)| Pair{ dist: distance(points[i], points[j]), i, j })
+            .collect();
+        v.sort();
+        v
+    }
+
+    fn random_points(n: usize, range: i64, seed: u64) -> Vec<Point> {
+        let mut rng = Lcg::new(seed);
+        let mut next = || rng.below(range as u64) as i64;
+        (0..n).map(|_| (next(), next(), next())).collect()
+    }
+
+    #[test]
+    fn test_pairs_order() {
+        // narrow range: many ties and duplicate points
+        for (n, range, seed) in [(0, 10, 1), (1, 10, 1), (2, 10, 1),
+                                 (60, 4, 2), (200, 1000, 3)] {
+            let points = random_points(n, range, seed);
+            let tree = KdTree::new(&points);
+            assert_eq!(tree.pairs().collect::<Vec<_>>(), all_pairs(&points));
+        }
+    }
+    #[test]
+    fn test_next_neighbours() {
+        let points = [(0, 0, 0), (3, 0, 0), (1, 0, 0), (0, 2, 0), (1, 0, 0)];
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.next_neighbours(0, None, 3), [(1, 2), (1, 4), (4, 3)]);
+        assert_eq!(tree.next_neighbours(0, Some((1, 2)), 2), [(1, 4), (4, 3)]);
+        assert_eq!(tree.next_neighbours(2, None, 9), [(0, 4), (5, 3)]);
+        assert_eq!(tree.next_neighbours(4, None, 9), []);
+    }
+    #[test]
+    fn test_pairs_lazy() {
+        let points = random_points(20_000, 1_000_000, 4);
+        let tree = KdTree::new(&points);
+        let first: Vec<Pair> = tree.pairs().take(100).collect();
+        assert!(first.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(first.len(), 100);
+    }
+}
@@ -0,0 +1,16 @@
+// Helpers shared by unit tests
+
+// small seeded LCG, good enough to scatter test data reproducibly
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg(seed)
+    }
+
+    // next value in 0..range
+    pub fn below(&mut self, range: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % range
+    }
+}
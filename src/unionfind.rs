@@ -0,0 +1,109 @@
+// Disjoint sets over 0..n, with path compression and union by size
+
+pub struct UnionFind {
+    parent: Vec<usize>,
+    // size of the set, only meaningful for roots
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    // n singleton sets
+    pub fn new(n: usize) -> UnionFind {
+        UnionFind{ parent: (0..n).collect(), size: vec![1; n], components: n }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // representative of the set containing x
+    pub fn find(&mut self, mut x: usize) -> usize {
+        // path halving: point every other node to its grandparent
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    // merge the sets containing a and b; false if already the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut ra, mut rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return false
+        }
+        // attach the smaller tree below the larger one
+        if self.size[ra] < self.size[rb] {
+            std::mem::swap(&mut ra, &mut rb);
+        }
+        self.parent[rb] = ra;
+        self.size[ra] += self.size[rb];
+        self.components -= 1;
+        true
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    // number of disjoint sets
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    // size of the set containing x
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn size(&mut self, x: usize) -> usize {
+        let r = self.find(x);
+        self.size[r]
+    }
+
+    // sizes of all sets, in no particular order
+    pub fn sizes(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|x| self.size[x])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.components(), 6);
+        assert!(!uf.is_empty() && UnionFind::new(0).is_empty());
+        assert!(uf.union(0, 1));
+        assert!(uf.union(2, 3));
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert!(uf.same(0, 3));
+        assert!(!uf.same(0, 4));
+        assert_eq!(uf.components(), 3);
+        assert_eq!(uf.size(2), 4);
+        assert_eq!(uf.size(5), 1);
+        let mut sizes = uf.sizes();
+        sizes.sort();
+        assert_eq!(sizes, [1, 1, 4]);
+    }
+    #[test]
+    fn test_long_chain() {
+        let n = 100_000;
+        let mut uf = UnionFind::new(n);
+        for k in 1..n {
+            uf.union(k - 1, k);
+        }
+        assert_eq!(uf.components(), 1);
+        assert_eq!(uf.size(0), n);
+    }
+}